@@ -1,17 +1,198 @@
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
 pub type UserId = usize;
 pub type RoomId = usize;
+pub type ChatId = u64;
+pub type ReportId = u64;
+pub type SanctionId = u64;
+pub type MatchId = u64;
+pub type TournamentId = u64;
+
+pub const ROOM_PAGE_SIZE: usize = 20;
 
 pub mod client {
     use super::*;
 
     #[derive(Serialize, Deserialize)]
     pub enum Message {
-        Chat { text: String, to: Option<UserId> },
-        CreateRoom { room: RoomBrief, },
+        /// `team` keeps a room line to the sender's team.
+        Chat {
+            text: String,
+            to: Option<UserId>,
+            #[serde(default)]
+            team: bool,
+        },
+        CreateRoom { room: RoomBrief, password: Option<String> },
         GetRoomDetail { room: RoomId },
-        JoinRoom { room: RoomId, },
+        QueryRooms { query: RoomQuery },
+        JoinRoom { room: RoomId, password: Option<String> },
+        Spectate { room: RoomId },
+        QuickJoin { criteria: MatchCriteria },
+        CancelQuickJoin,
         QuitRoom,
+        InviteToRoom { user: UserId },
+        AcceptInvite { room: RoomId },
+        DeclineInvite { room: RoomId },
+        RequestFriend { user: UserId },
+        AcceptFriend { user: UserId },
+        RemoveFriend { user: UserId },
+        Block { user: UserId },
+        Unblock { user: UserId },
+        Report { user: UserId, reason: String, message_id: ChatId },
+        /// Only the room owner may start a round.
+        StartGame,
+        GameAction { action: GameAction },
+        /// Only the room owner may add or remove bots.
+        AddBot,
+        RemoveBot { bot: UserId },
+        CreateTournament { tournament: NewTournament },
+        JoinTournament { tournament: TournamentId },
+        /// Withdraws before the start, or forfeits every match left after.
+        LeaveTournament { tournament: TournamentId },
+        /// Only the organizer or an admin may start or cancel one.
+        StartTournament { tournament: TournamentId },
+        CancelTournament { tournament: TournamentId },
+        /// `None` sits out of the teams.
+        ChooseTeam { team: Option<Team> },
+        /// Only the room owner may shuffle or clear the teams.
+        ShuffleTeams,
+        ClearTeams,
+    }
+}
+
+pub mod api {
+    use super::*;
+
+    #[derive(Serialize, Deserialize, Clone, PartialEq)]
+    pub struct Credentials {
+        pub name: String,
+        pub password: String,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, PartialEq)]
+    pub struct Login {
+        pub token: String,
+        pub user: UserBrief,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+    pub enum AuthError {
+        InvalidName,
+        InvalidPassword,
+        NameTaken,
+        WrongCredentials,
+        /// The owner already has as many bot accounts as allowed.
+        TooManyBots,
+        /// Too many logins or sign-ups came from the address, or for the
+        /// name, in a short time.
+        TooManyAttempts,
+    }
+
+    /// Body of `POST /api/bots`, sent with the owner's token.
+    #[derive(Serialize, Deserialize, Clone, PartialEq)]
+    pub struct NewBot {
+        pub name: String,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+    pub enum AdminError {
+        Unauthorized,
+        NotFound,
+    }
+
+    /// A chat line as the server remembers it.
+    #[derive(Serialize, Deserialize, Clone, PartialEq)]
+    pub struct ChatRecord {
+        pub id: ChatId,
+        pub from: UserId,
+        pub to: Option<UserId>,
+        pub room: Option<RoomId>,
+        pub text: String,
+        pub scope: ChatScope,
+        pub time: u64,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, PartialEq)]
+    pub struct Report {
+        pub id: ReportId,
+        pub reporter: UserId,
+        pub user: UserId,
+        pub reason: String,
+        pub message_id: ChatId,
+        /// Chat around the reported line.
+        pub context: Vec<ChatRecord>,
+        pub time: u64,
+        pub resolution: Option<Resolution>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, PartialEq)]
+    pub struct Resolution {
+        pub by: UserId,
+        pub note: String,
+        pub time: u64,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, PartialEq)]
+    pub struct Resolve {
+        pub note: String,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+    pub enum SanctionTarget {
+        Account(UserId),
+        Ip(String),
+    }
+
+    #[derive(Serialize, Deserialize, Clone, PartialEq)]
+    pub struct Sanction {
+        pub id: SanctionId,
+        pub target: SanctionTarget,
+        pub kind: SanctionKind,
+        pub reason: String,
+        pub by: UserId,
+        pub time: u64,
+        pub until: u64,
+        pub lifted: bool,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, PartialEq)]
+    pub struct NewSanction {
+        pub target: SanctionTarget,
+        pub kind: SanctionKind,
+        pub reason: String,
+        /// Seconds from now.
+        pub duration: u64,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, PartialEq)]
+    pub enum AuditAction {
+        Sanctioned(Sanction),
+        Lifted(SanctionId),
+        ResolvedReport(ReportId),
+        Kicked(UserId),
+        Maintenance { seconds: u64 },
+        MaintenanceEnded,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, PartialEq)]
+    pub struct Maintenance {
+        /// Seconds until sessions are closed.
+        pub seconds: u64,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, PartialEq)]
+    pub struct AuditEntry {
+        pub by: UserId,
+        pub time: u64,
+        pub action: AuditAction,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, PartialEq)]
+    pub struct LeaderboardEntry {
+        pub user: UserId,
+        pub name: String,
+        pub rating: i32,
+        pub games: u32,
     }
 }
 
@@ -27,17 +208,26 @@ pub mod server {
         },
         Welcome {
             id: UserId,
+            /// Lets the client pick this session up again after a restart.
+            resume: String,
             users: Box<[UserBrief]>,
-            rooms: Box<[RoomBrief]>,
+            rooms: RoomPage,
+            /// Games the server can host. Word chain needs a dictionary.
+            games: Vec<GameType>,
         },
         Disconnected {
             id: UserId,
         },
+        /// Sent right before the server closes the connection.
+        Disconnecting {
+            reason: DisconnectReason,
+        },
         Alert(Alert),
         Chat {
+            id: ChatId,
             from: UserId,
             text: String,
-            whisper: bool,
+            scope: ChatScope,
         },
         NewRoom {
             room: RoomBrief,
@@ -45,6 +235,38 @@ pub mod server {
         DestroyRoom {
             room: RoomId,
         },
+        RoomChanged {
+            room: RoomBrief,
+        },
+        RoomPage {
+            page: RoomPage,
+        },
+        Matchmaking {
+            status: MatchStatus,
+        },
+        Invited {
+            from: UserId,
+            room: RoomBrief,
+        },
+        InviteExpired {
+            room: RoomId,
+        },
+        Friends {
+            friends: Vec<Friend>,
+            requests: Vec<UserBrief>,
+        },
+        FriendRequested {
+            from: UserBrief,
+        },
+        FriendChanged {
+            friend: Friend,
+        },
+        FriendRemoved {
+            id: UserId,
+        },
+        BlockList {
+            users: Vec<UserId>,
+        },
         RoomDetail {
             room: Room,
         },
@@ -53,7 +275,34 @@ pub mod server {
         },
         RoomUpdate {
             room: Room,
-        }
+        },
+        GameUpdate {
+            view: GameView,
+        },
+        /// A round is over and on record.
+        GameResult {
+            result: MatchRecord,
+        },
+        /// Something happened in the game that a view alone doesn't show.
+        GameEvent {
+            event: GameEvent,
+        },
+        /// The room's current deadline, if any. `now` is the server clock
+        /// at sending, so clients can make up for their own.
+        Timer {
+            deadline: Option<Deadline>,
+            now: u64,
+        },
+        /// Every tournament, sent once after `Welcome`.
+        Tournaments {
+            tournaments: Vec<Tournament>,
+        },
+        TournamentChanged {
+            tournament: Tournament,
+        },
+        TournamentClosed {
+            id: TournamentId,
+        },
     }
 }
 
@@ -62,12 +311,95 @@ pub enum Alert {
     TargetNotFound,
     Join { user: UserId },
     Quit { user: UserId },
+    Spectate { user: UserId },
+    RoomFull,
+    WrongPassword,
+    InviteSent { user: UserId },
+    InviteUnavailable,
+    InviteDeclined { user: UserId },
+    InviteExpired { user: UserId },
+    AccountRequired,
+    FriendRequestSent { user: UserId },
+    ReportReceived,
+    Sanctioned {
+        kind: SanctionKind,
+        reason: String,
+        until: u64,
+    },
+    /// Seconds until maintenance, zero once it has started.
+    Maintenance { seconds: u64 },
+    NotEnoughPlayers,
+    GameInProgress,
+    InvalidMove,
+    /// The server lacks what the game needs, such as a dictionary.
+    GameUnavailable,
+    /// The room only takes accounts rated within the range.
+    RatingOutOfRange { range: RatingRange },
+    /// Messages are coming in faster than the connection may send them, so
+    /// some were dropped.
+    RateLimited,
+    TournamentFull,
+    /// The account, or the server, already has as many tournaments open as
+    /// it may.
+    TooManyTournaments,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum DisconnectReason {
+    Timeout,
+    Kicked,
+    Banned,
+    /// The server is shutting down or going into maintenance.
+    Shutdown,
+    ProtocolError,
+    DuplicateLogin,
+}
+
+impl DisconnectReason {
+    /// The WebSocket close code sent along with the reason.
+    pub fn code(self) -> u16 {
+        match self {
+            DisconnectReason::Shutdown => 1001,
+            DisconnectReason::ProtocolError => 1002,
+            DisconnectReason::Timeout => 4000,
+            DisconnectReason::Kicked => 4001,
+            DisconnectReason::Banned => 4002,
+            DisconnectReason::DuplicateLogin => 4003,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum SanctionKind {
+    Mute,
+    /// May not create rooms.
+    NoRooms,
+    /// May not connect at all.
+    Ban,
+}
+
+impl SanctionKind {
+    /// Whether a sanction of this kind also forbids `other`.
+    pub fn covers(self, other: SanctionKind) -> bool {
+        self == other || self == SanctionKind::Ban
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum ChatScope {
+    Public,
+    Whisper,
+    Spectator,
+    Team,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq)]
 pub struct UserBrief {
     pub id: UserId,
     pub name: String,
+    /// Not a person: a seat the server plays, or a bot account.
+    #[serde(default)]
+    pub bot: bool,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -80,12 +412,351 @@ pub struct User {
 pub struct RoomBrief {
     pub id: RoomId,
     pub title: String,
+    pub owner: UserId,
+    pub members: usize,
+    pub capacity: usize,
+    pub playing: bool,
+    pub separate_spectators: bool,
+    pub locked: bool,
+    #[serde(default)]
+    pub game: GameKind,
+    #[serde(default)]
+    pub rating: Option<RatingRange>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
 pub struct Room {
     pub id: RoomId,
     pub title: String,
     pub owner: UserId,
     pub members: Vec<UserId>,
-}
\ No newline at end of file
+    pub spectators: Vec<UserId>,
+    pub capacity: usize,
+    pub separate_spectators: bool,
+    pub game: GameKind,
+    pub playing: bool,
+    /// Members on a team. Members left out play for themselves.
+    #[serde(default)]
+    pub teams: Vec<(UserId, Team)>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Team {
+    Red,
+    Blue,
+}
+
+/// Which game a room plays, picked when it is created.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum GameKind {
+    /// Everyone rolls once and the highest roll wins.
+    Dice,
+    /// 끝말잇기. Players who run out of time are eliminated.
+    WordChain {
+        /// Accept 두음법칙 variants of the last syllable, such as 녀 → 여.
+        initial_sound_rule: bool,
+        turn_seconds: u64,
+    },
+}
+
+impl Default for GameKind {
+    fn default() -> Self {
+        GameKind::Dice
+    }
+}
+
+impl GameKind {
+    pub fn game_type(self) -> GameType {
+        match self {
+            GameKind::Dice => GameType::Dice,
+            GameKind::WordChain { .. } => GameType::WordChain,
+        }
+    }
+}
+
+/// A game without its options, which is what ratings are kept per.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum GameType {
+    Dice,
+    WordChain,
+}
+
+/// Inclusive bounds on the ratings a room lets in.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct RatingRange {
+    pub min: i32,
+    pub max: i32,
+}
+
+impl RatingRange {
+    pub fn contains(self, rating: i32) -> bool {
+        self.min <= rating && rating <= self.max
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub enum GameAction {
+    Roll,
+    Word { word: String },
+}
+
+/// The game as one player, or a spectator, gets to see it.
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub enum GameView {
+    Dice { rolls: Vec<(UserId, Option<u8>)> },
+    WordChain {
+        /// Players in turn order, with whether they are still in.
+        players: Vec<(UserId, bool)>,
+        turn: Option<UserId>,
+        /// The word the next one has to follow.
+        word: String,
+        /// Syllables the next word may start with.
+        starts: Vec<char>,
+        initial_sound_rule: bool,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub enum GameEvent {
+    WordAccepted { player: UserId, word: String },
+    WordRejected { player: UserId, word: String, reason: WordRejection },
+    Eliminated { player: UserId },
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum WordRejection {
+    /// Doesn't follow the last syllable.
+    WrongStart,
+    NotInDictionary,
+    AlreadyUsed,
+    TooShort,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum TimerKind {
+    /// Before a round starts.
+    Countdown,
+    Turn,
+    /// Between the end of a round and the next start.
+    Interval,
+}
+
+/// A deadline on the server, in milliseconds since the Unix epoch.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct Deadline {
+    pub kind: TimerKind,
+    pub start: u64,
+    pub end: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct MatchPlayer {
+    pub id: UserId,
+    /// As it was when the round started.
+    pub name: String,
+    pub score: u32,
+    /// A team wins together when any of its players does.
+    #[serde(default)]
+    pub team: Option<Team>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct MatchRecord {
+    pub id: MatchId,
+    pub room: RoomId,
+    pub game: GameKind,
+    /// Everyone who started the round, in seat order.
+    pub players: Vec<MatchPlayer>,
+    /// Whom the game itself picked. Ratings and brackets go by these alone.
+    pub winners: Vec<UserId>,
+    /// Teammates of the winners, who share in the win.
+    #[serde(default)]
+    pub teammates: Vec<UserId>,
+    pub time: u64,
+    /// Seconds from the start of the round to its end.
+    pub duration: u64,
+}
+
+impl MatchRecord {
+    /// Whether `id` won, on their own or with their team.
+    pub fn won(&self, id: UserId) -> bool {
+        self.winners.contains(&id) || self.teammates.contains(&id)
+    }
+}
+
+/// A message a room sent during a round, `at` milliseconds into it.
+#[derive(Serialize, Deserialize)]
+pub struct ReplayFrame {
+    pub at: u64,
+    pub message: server::Message,
+}
+
+/// A round as spectators saw it, ending with its `GameResult`.
+#[derive(Serialize, Deserialize)]
+pub struct Replay {
+    pub record: MatchRecord,
+    pub frames: Vec<ReplayFrame>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum Elimination {
+    Single,
+    /// Players drop to the losers' bracket after their first loss. The grand
+    /// final is played again if the winners' side loses it.
+    Double,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum TournamentState {
+    SignUp,
+    Running,
+    Finished,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum BracketSide {
+    Winners,
+    Losers,
+    Final,
+}
+
+/// One side of a bracket match.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum Slot {
+    /// Filled in once an earlier match ends.
+    Waiting,
+    Player(UserId),
+    /// Nobody is coming, so the other side goes through.
+    Bye,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct BracketMatch {
+    pub side: BracketSide,
+    /// Counted from 1 within its side.
+    pub round: u32,
+    pub slots: [Slot; 2],
+    /// `Bye` when neither side showed up.
+    pub winner: Option<Slot>,
+    /// The room opened for it, which may be gone by now.
+    pub room: Option<RoomId>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct Tournament {
+    pub id: TournamentId,
+    pub name: String,
+    pub game: GameKind,
+    pub elimination: Elimination,
+    /// Who made it, and may start or cancel it.
+    pub organizer: UserId,
+    pub capacity: usize,
+    pub entrants: Vec<UserBrief>,
+    /// Entrants who left after the start and lose every match left.
+    pub forfeited: Vec<UserId>,
+    pub state: TournamentState,
+    /// Empty until the start. A match only ever waits on earlier ones.
+    pub matches: Vec<BracketMatch>,
+    pub champion: Option<UserId>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct NewTournament {
+    pub name: String,
+    pub game: GameKind,
+    pub elimination: Elimination,
+    /// Zero for the most the server allows.
+    pub capacity: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum RoomFilter {
+    All,
+    Joinable,
+    Full,
+    Playing,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum RoomSort {
+    Id,
+    Title,
+    Members,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct RoomQuery {
+    pub title: String,
+    pub filter: RoomFilter,
+    pub sort: RoomSort,
+    pub page: usize,
+}
+
+impl Default for RoomQuery {
+    fn default() -> Self {
+        Self {
+            title: String::new(),
+            filter: RoomFilter::All,
+            sort: RoomSort::Id,
+            page: 0,
+        }
+    }
+}
+
+impl RoomQuery {
+    pub fn matches(&self, room: &RoomBrief) -> bool {
+        let full = room.members >= room.capacity;
+        let filtered = match self.filter {
+            RoomFilter::All => true,
+            RoomFilter::Joinable => !full && !room.playing,
+            RoomFilter::Full => full,
+            RoomFilter::Playing => room.playing,
+        };
+        filtered && room.title.to_lowercase().contains(&self.title.trim().to_lowercase())
+    }
+
+    /// Orders rooms the same way on both ends, falling back to the id so
+    /// that the list never shuffles between updates.
+    pub fn compare(&self, a: &RoomBrief, b: &RoomBrief) -> Ordering {
+        let order = match self.sort {
+            RoomSort::Id => Ordering::Equal,
+            RoomSort::Title => a.title.cmp(&b.title),
+            RoomSort::Members => b.members.cmp(&a.members),
+        };
+        order.then(a.id.cmp(&b.id))
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct RoomPage {
+    pub query: RoomQuery,
+    pub rooms: Box<[RoomBrief]>,
+    pub total: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct MatchCriteria {
+    pub capacity: Option<usize>,
+    pub skill: Option<i32>,
+    pub region: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub enum MatchStatus {
+    Queued { estimated_wait: u64 },
+    Matched { room: RoomId },
+    Cancelled,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum Presence {
+    Offline,
+    Lobby,
+    InRoom(RoomId),
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct Friend {
+    pub user: UserBrief,
+    pub presence: Presence,
+}
@@ -1,8 +1,14 @@
 #![recursion_limit = "256"]
-use bridge::{Alert, UserBrief, UserId, RoomBrief, RoomId, Room};
+use bridge::api::{AuthError, Credentials, LeaderboardEntry, Login};
+use bridge::{Alert, ChatId, ChatScope, Deadline, DisconnectReason, GameAction, GameEvent, GameType, GameView, MatchId, MatchRecord, NewTournament, Replay, ReplayFrame, SanctionKind, Team, Tournament, TournamentId, WordRejection, Friend, MatchCriteria, MatchStatus, UserBrief, UserId, RoomBrief, RoomId, Room, RoomPage, RoomQuery, ROOM_PAGE_SIZE};
 use failure::Error;
-use std::collections::HashMap;
-use yew::format::Json;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::time::Duration;
+use stdweb::web::Date;
+use yew::format::{Json, Nothing, Text};
+use yew::services::fetch::{FetchService, FetchTask, Request, Response};
+use yew::services::storage::{Area, StorageService};
+use yew::services::timeout::{TimeoutService, TimeoutTask};
 use yew::services::websocket::{WebSocketService, WebSocketStatus, WebSocketTask};
 use yew::{html, Component, ComponentLink, Html, Renderable, ShouldRender};
 
@@ -13,44 +19,204 @@ pub struct Model {
     ws: Option<WebSocketTask>,
     link: ComponentLink<Self>,
     socket: WebSocketService,
+    fetch: FetchService,
+    fetching: Option<FetchTask>,
+    fetching_leaderboard: Option<FetchTask>,
+    fetching_matches: Option<FetchTask>,
+    fetching_replay: Option<FetchTask>,
+    timeout: TimeoutService,
+    storage: StorageService,
+    session_storage: StorageService,
+    token: Option<String>,
+    resume: Option<String>,
+    login: Option<Option<String>>,
     connected: Option<bool>,
+    disconnect: Option<DisconnectReason>,
     client: Option<Client>,
+    playback: Option<Playback>,
 
     menu: Option<MenuItem>,
 }
 
+/// A replay fed back through `update` as if the server sent it.
+struct Playback {
+    record: MatchRecord,
+    frames: VecDeque<ReplayFrame>,
+    /// Local clock when playback began.
+    started: f64,
+    next: Option<TimeoutTask>,
+}
+
 struct Client {
     id: UserId,
     room: Option<Room>,
     users: HashMap<UserId, UserBrief>,
     rooms: HashMap<RoomId, RoomBrief>,
+    query: RoomQuery,
+    room_total: usize,
     chats: Vec<Chat>,
+    hovered: Option<RoomId>,
+    preview: Option<Room>,
+    matching: Option<u64>,
+    invites: Vec<(UserId, RoomBrief)>,
+    friends: HashMap<UserId, Friend>,
+    requests: Vec<UserBrief>,
+    blocked: HashSet<UserId>,
+    game: Option<GameView>,
+    timer: Option<Deadline>,
+    /// Server clock minus local clock, for the countdown.
+    clock_offset: i64,
+    leaderboard_game: GameType,
+    leaderboard: Vec<LeaderboardEntry>,
+    matches: Vec<MatchRecord>,
+    tournaments: BTreeMap<TournamentId, Tournament>,
+    /// Games the server hosts, the only ones offered for new rooms.
+    games: Vec<GameType>,
 }
 
 pub enum Msg {
     WebResponse(Result<bridge::server::Message, Error>),
     WebRequest(bridge::client::Message),
+    LogIn(Credentials, bool),
+    LoggedIn(Result<Login, Option<AuthError>>),
     Connect,
     Connected,
     Failed,
-    SendChat(String),
+    SendChat(String, bool),
     OpenMenu(MenuItem),
     MenuEvent(MenuEvent),
     RoomEvent(RoomEvent),
+    UserClicked(UserId),
+    FriendEvent(FriendEvent),
+    TournamentEvent(TournamentEvent),
+    LoadLeaderboard(GameType),
+    GotLeaderboard(GameType, Vec<LeaderboardEntry>),
+    LoadMatches,
+    GotMatches(Vec<MatchRecord>),
+    WatchReplay(MatchId),
+    GotReplay(Option<Replay>),
+    ReplayStep,
+    StopReplay,
+}
+
+pub enum FriendEvent {
+    Request(UserId),
+    Accept(UserId),
+    Remove(UserId),
+    Block(UserId, bool),
+}
+
+pub enum TournamentEvent {
+    Join(TournamentId),
+    Leave(TournamentId),
+    Start(TournamentId),
+    Cancel(TournamentId),
 }
 
 pub enum MenuItem {
     CreateRoom,
+    JoinLocked(RoomId),
+    Report(UserId, ChatId),
+    Result(MatchRecord),
+    CreateTournament,
+    Bracket(TournamentId),
 }
 
 pub enum MenuEvent {
     Cancel,
-    CreateRoom(RoomBrief),
+    CreateRoom(RoomBrief, Option<String>),
+    CreateTournament(NewTournament),
+    JoinLocked(RoomId, String),
+    Report(UserId, ChatId, String),
+    AcceptInvite(RoomId),
+    DeclineInvite(RoomId),
 }
 
 pub enum RoomEvent {
     Quit,
     Join(RoomId),
+    Spectate(RoomId),
+    Hover(Option<RoomId>),
+    Query(RoomQuery),
+    QuickJoin,
+    CancelQuickJoin,
+    StartGame,
+    GameAction(GameAction),
+    AddBot,
+    RemoveBot(UserId),
+    ChooseTeam(Option<Team>),
+    ShuffleTeams,
+    ClearTeams,
+}
+
+const TOKEN_KEY: &str = "kiwitu.token";
+const RESUME_KEY: &str = "kiwitu.resume";
+
+fn auth_error_text(error: Option<AuthError>) -> String {
+    match error {
+        Some(AuthError::InvalidName) => "이름은 2~16자여야 합니다.".into(),
+        Some(AuthError::InvalidPassword) => "비밀번호는 4자 이상이어야 합니다.".into(),
+        Some(AuthError::NameTaken) => "이미 사용 중인 이름입니다.".into(),
+        Some(AuthError::WrongCredentials) => "이름 또는 비밀번호가 맞지 않습니다.".into(),
+        Some(AuthError::TooManyBots) => "더 이상 봇 계정을 만들 수 없습니다.".into(),
+        Some(AuthError::TooManyAttempts) => "시도가 너무 많습니다. 잠시 후 다시 해 주세요.".into(),
+        None => "서버에 연결할 수 없습니다.".into(),
+    }
+}
+
+/// 유닉스 시간을 한국 시간으로
+fn format_time(time: u64) -> String {
+    let time = time + 9 * 3600;
+    let (days, seconds) = (time / 86400, time % 86400);
+    // 1970-03-01부터 센 날짜를 그레고리력으로
+    let days = days as i64 + 719468;
+    let era = days / 146097;
+    let day_of_era = days - era * 146097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{}-{:02}-{:02} {:02}:{:02}", year, month, day, seconds / 3600, seconds % 3600 / 60)
+}
+
+fn maintenance_text(seconds: u64) -> String {
+    if seconds > 0 {
+        format!("{}초 후 서버 점검이 시작됩니다. 진행 중인 게임을 정리해 주세요.", seconds)
+    } else {
+        "서버 점검 중입니다. 잠시 후 다시 접속해 주세요.".into()
+    }
+}
+
+fn disconnect_text(reason: Option<DisconnectReason>) -> String {
+    match reason {
+        Some(DisconnectReason::Timeout) => "응답이 없어 연결이 끊어졌습니다.".into(),
+        Some(DisconnectReason::Kicked) => "관리자에 의해 연결이 끊어졌습니다.".into(),
+        Some(DisconnectReason::Banned) => "접속 금지 제재로 연결이 끊어졌습니다.".into(),
+        Some(DisconnectReason::Shutdown) => "서버 점검으로 연결이 끊어졌습니다.".into(),
+        Some(DisconnectReason::ProtocolError) => "잘못된 요청을 보내 연결이 끊어졌습니다.".into(),
+        Some(DisconnectReason::DuplicateLogin) => "다른 곳에서 같은 계정으로 접속했습니다.".into(),
+        None => "서버와 연결이 끊어졌습니다.".into(),
+    }
+}
+
+fn rejection_text(reason: WordRejection) -> &'static str {
+    match reason {
+        WordRejection::WrongStart => "끝 글자로 시작하지 않습니다",
+        WordRejection::NotInDictionary => "사전에 없는 단어입니다",
+        WordRejection::AlreadyUsed => "이미 나온 단어입니다",
+        WordRejection::TooShort => "두 글자 이상이어야 합니다",
+    }
+}
+
+fn sanction_text(kind: SanctionKind, reason: &str, until: u64) -> String {
+    let kind = match kind {
+        SanctionKind::Mute => "채팅 금지",
+        SanctionKind::NoRooms => "방 만들기 금지",
+        SanctionKind::Ban => "접속 금지",
+    };
+    format!("{} 제재 중입니다. (사유: {}, {}까지)", kind, reason, format_time(until))
 }
 
 impl Component for Model {
@@ -58,31 +224,207 @@ impl Component for Model {
     type Properties = ();
 
     fn create(_: Self::Properties, mut link: ComponentLink<Self>) -> Self {
-        link.send_self(Msg::Connect);
+        let mut storage = StorageService::new(Area::Local);
+        let Json(token): Json<Result<String, Error>> = storage.restore(TOKEN_KEY);
+        let token = token.ok();
+        // 탭마다 따로 이어 붙도록 세션 저장소에
+        let mut session_storage = StorageService::new(Area::Session);
+        let Json(resume): Json<Result<String, Error>> = session_storage.restore(RESUME_KEY);
+        let resume = resume.ok();
+        // 저장된 토큰이 있으면 바로 접속
+        let login = if token.is_some() {
+            link.send_self(Msg::Connect);
+            None
+        } else {
+            Some(None)
+        };
         Model {
             ws: None,
             link,
             socket: WebSocketService::new(),
+            fetch: FetchService::new(),
+            fetching: None,
+            fetching_leaderboard: None,
+            fetching_matches: None,
+            fetching_replay: None,
+            timeout: TimeoutService::new(),
+            storage,
+            session_storage,
+            token,
+            resume,
+            login,
             connected: None,
+            disconnect: None,
             client: None,
+            playback: None,
             menu: None,
         }
     }
 
     fn update(&mut self, msg: Self::Message) -> ShouldRender {
         match msg {
+            Msg::LogIn(credentials, register) => {
+                let url = if register { "/api/register" } else { "/api/login" };
+                let request = Request::post(url)
+                    .header("Content-Type", "application/json")
+                    .body(Json(&credentials))
+                    .expect("Failed to build request");
+                let callback = self.link.send_back(|response: Response<Text>| {
+                    let (meta, body) = response.into_parts();
+                    if meta.status.is_success() {
+                        let Json(login): Json<Result<Login, Error>> = body.into();
+                        Msg::LoggedIn(login.map_err(|_| None))
+                    } else {
+                        let Json(error): Json<Result<AuthError, Error>> = body.into();
+                        Msg::LoggedIn(Err(error.ok()))
+                    }
+                });
+                self.fetching = Some(self.fetch.fetch(request, callback));
+                false
+            }
+            Msg::LoggedIn(result) => {
+                self.fetching = None;
+                match result {
+                    Ok(login) => {
+                        self.storage.store(TOKEN_KEY, Json(&login.token));
+                        self.token = Some(login.token);
+                        self.login = None;
+                        self.link.send_self(Msg::Connect);
+                    }
+                    Err(error) => {
+                        self.login = Some(Some(auth_error_text(error)));
+                    }
+                }
+                true
+            }
+            Msg::LoadLeaderboard(game) => {
+                let request = Request::get(format!("/api/leaderboard?game={:?}", game))
+                    .body(Nothing)
+                    .expect("Failed to build request");
+                let callback = self.link.send_back(move |response: Response<Text>| {
+                    let Json(entries): Json<Result<Vec<LeaderboardEntry>, Error>> = response.into_body().into();
+                    Msg::GotLeaderboard(game, entries.unwrap_or_default())
+                });
+                self.fetching_leaderboard = Some(self.fetch.fetch(request, callback));
+                false
+            }
+            Msg::GotLeaderboard(game, entries) => {
+                self.fetching_leaderboard = None;
+                if let Some(client) = self.client.as_mut() {
+                    client.leaderboard_game = game;
+                    client.leaderboard = entries;
+                }
+                true
+            }
+            Msg::LoadMatches => {
+                let id = match &self.client {
+                    Some(client) => client.id,
+                    None => return false,
+                };
+                let request = Request::get(format!("/api/users/{}/matches", id))
+                    .body(Nothing)
+                    .expect("Failed to build request");
+                let callback = self.link.send_back(|response: Response<Text>| {
+                    let Json(matches): Json<Result<Vec<MatchRecord>, Error>> = response.into_body().into();
+                    Msg::GotMatches(matches.unwrap_or_default())
+                });
+                self.fetching_matches = Some(self.fetch.fetch(request, callback));
+                false
+            }
+            Msg::GotMatches(matches) => {
+                self.fetching_matches = None;
+                if let Some(client) = self.client.as_mut() {
+                    client.matches = matches;
+                }
+                true
+            }
+            Msg::WatchReplay(id) => {
+                let request = Request::get(format!("/api/matches/{}/replay", id))
+                    .body(Nothing)
+                    .expect("Failed to build request");
+                let callback = self.link.send_back(|response: Response<Text>| {
+                    let Json(replay): Json<Result<Replay, Error>> = response.into_body().into();
+                    Msg::GotReplay(replay.ok())
+                });
+                self.fetching_replay = Some(self.fetch.fetch(request, callback));
+                false
+            }
+            Msg::GotReplay(replay) => {
+                self.fetching_replay = None;
+                let client = match self.client.as_mut() {
+                    Some(client) => client,
+                    None => return false,
+                };
+                // 방 안에서는 실제 게임과 섞이므로 보지 않음
+                if client.room.is_some() {
+                    return false;
+                }
+                match replay {
+                    Some(replay) => {
+                        client.game = None;
+                        client.timer = None;
+                        self.playback = Some(Playback {
+                            record: replay.record,
+                            frames: replay.frames.into(),
+                            started: Date::now(),
+                            next: None,
+                        });
+                        self.link.send_self(Msg::ReplayStep);
+                    }
+                    None => client.chats.push(Chat::Alert("다시 보기를 불러올 수 없습니다.".into())),
+                }
+                true
+            }
+            Msg::ReplayStep => {
+                let mut due = vec![];
+                if let Some(playback) = self.playback.as_mut() {
+                    let elapsed = Date::now() - playback.started;
+                    while playback.frames.front().map_or(false, |frame| frame.at as f64 <= elapsed) {
+                        due.push(playback.frames.pop_front().unwrap().message);
+                    }
+                    playback.next = match playback.frames.front() {
+                        Some(frame) => {
+                            let delay = Duration::from_millis((frame.at as f64 - elapsed) as u64);
+                            Some(self.timeout.spawn(delay, self.link.send_back(|_| Msg::ReplayStep)))
+                        }
+                        None => None,
+                    };
+                }
+                for message in due {
+                    self.update(Msg::WebResponse(Ok(message)));
+                }
+                true
+            }
+            Msg::StopReplay => {
+                self.playback = None;
+                if let Some(client) = self.client.as_mut() {
+                    client.game = None;
+                    client.timer = None;
+                }
+                true
+            }
             Msg::Connect => {
+                self.login = None;
+                self.disconnect = None;
                 let callback = self.link.send_back(|Json(msg)| Msg::WebResponse(msg));
                 let notification = self.link.send_back(|status| match status {
                     WebSocketStatus::Opened => Msg::Connected,
                     _ => Msg::Failed,
                 });
+                let mut query = vec![];
+                if let Some(token) = &self.token {
+                    query.push(format!("token={}", token));
+                }
+                if let Some(resume) = &self.resume {
+                    query.push(format!("resume={}", resume));
+                }
+                let url = format!("ws://127.0.0.1:8080/ws/?{}", query.join("&"));
                 self.ws = Some(self.socket.connect(
-                    "ws://127.0.0.1:8080/ws/",
+                    &url,
                     callback,
                     notification,
                 ));
-                false
+                true
             }
             Msg::Connected => {
                 self.connected = Some(true);
@@ -90,13 +432,20 @@ impl Component for Model {
             }
             Msg::Failed => {
                 self.connected = Some(false);
+                self.ws = None;
+                self.client = None;
+                // 거절 사유가 이미 떠 있으면 그대로 둔다
+                if self.login.is_none() {
+                    self.login = Some(Some(disconnect_text(self.disconnect.take())));
+                }
                 true
             }
-            Msg::SendChat(text) => {
+            Msg::SendChat(text, team) => {
                 self.link
                     .send_self(Msg::WebRequest(bridge::client::Message::Chat {
                         text,
                         to: None,
+                        team,
                     }));
                 false
             }
@@ -110,27 +459,163 @@ impl Component for Model {
                         self.menu = None;
                         true
                     }
-                    MenuEvent::CreateRoom(room) => {
+                    MenuEvent::CreateRoom(room, password) => {
                         self.menu = None;
                         self.link.send_self(Msg::WebRequest(bridge::client::Message::CreateRoom {
                             room,
+                            password,
                         }));
                         true
                     }
+                    MenuEvent::CreateTournament(tournament) => {
+                        self.menu = None;
+                        self.link.send_self(Msg::WebRequest(bridge::client::Message::CreateTournament { tournament }));
+                        true
+                    }
+                    MenuEvent::JoinLocked(room, password) => {
+                        self.menu = None;
+                        self.link.send_self(Msg::WebRequest(bridge::client::Message::JoinRoom {
+                            room,
+                            password: Some(password),
+                        }));
+                        true
+                    }
+                    MenuEvent::Report(user, message_id, reason) => {
+                        self.menu = None;
+                        self.link.send_self(Msg::WebRequest(bridge::client::Message::Report {
+                            user,
+                            reason,
+                            message_id,
+                        }));
+                        true
+                    }
+                    MenuEvent::AcceptInvite(room) => {
+                        let client = self.client.as_mut().unwrap();
+                        client.invites.clear();
+                        self.link.send_self(Msg::WebRequest(bridge::client::Message::AcceptInvite { room }));
+                        true
+                    }
+                    MenuEvent::DeclineInvite(room) => {
+                        let client = self.client.as_mut().unwrap();
+                        client.invites.retain(|(_, invite)| invite.id != room);
+                        self.link.send_self(Msg::WebRequest(bridge::client::Message::DeclineInvite { room }));
+                        true
+                    }
                 }
             }
+            Msg::UserClicked(user) => {
+                let client = self.client.as_ref().unwrap();
+                // 방 안에서는 초대
+                if client.room.is_some() && user != client.id {
+                    self.link.send_self(Msg::WebRequest(bridge::client::Message::InviteToRoom { user }));
+                }
+                false
+            }
+            Msg::FriendEvent(event) => {
+                let message = match event {
+                    FriendEvent::Request(user) => bridge::client::Message::RequestFriend { user },
+                    FriendEvent::Accept(user) => bridge::client::Message::AcceptFriend { user },
+                    FriendEvent::Remove(user) => {
+                        let client = self.client.as_mut().unwrap();
+                        client.requests.retain(|request| request.id != user);
+                        bridge::client::Message::RemoveFriend { user }
+                    }
+                    FriendEvent::Block(user, true) => bridge::client::Message::Block { user },
+                    FriendEvent::Block(user, false) => bridge::client::Message::Unblock { user },
+                };
+                self.link.send_self(Msg::WebRequest(message));
+                true
+            }
+            Msg::TournamentEvent(event) => {
+                let message = match event {
+                    TournamentEvent::Join(tournament) => bridge::client::Message::JoinTournament { tournament },
+                    TournamentEvent::Leave(tournament) => bridge::client::Message::LeaveTournament { tournament },
+                    TournamentEvent::Start(tournament) => bridge::client::Message::StartTournament { tournament },
+                    TournamentEvent::Cancel(tournament) => bridge::client::Message::CancelTournament { tournament },
+                };
+                self.link.send_self(Msg::WebRequest(message));
+                false
+            }
             Msg::RoomEvent(event) => {
                 match event {
                     RoomEvent::Quit => {
                         let client = self.client.as_mut().unwrap();
                         client.room = None;
+                        client.game = None;
+                        client.timer = None;
                         self.link.send_self(Msg::WebRequest(bridge::client::Message::QuitRoom));
                         true
                     }
                     RoomEvent::Join(room) => {
-                        self.link.send_self(Msg::WebRequest(bridge::client::Message::JoinRoom { room }));
+                        let client = self.client.as_ref().unwrap();
+                        if client.rooms.get(&room).map_or(false, |room| room.locked) {
+                            self.menu = Some(MenuItem::JoinLocked(room));
+                            true
+                        } else {
+                            self.link.send_self(Msg::WebRequest(bridge::client::Message::JoinRoom {
+                                room,
+                                password: None,
+                            }));
+                            false
+                        }
+                    }
+                    RoomEvent::Spectate(room) => {
+                        self.link.send_self(Msg::WebRequest(bridge::client::Message::Spectate { room }));
+                        false
+                    }
+                    RoomEvent::QuickJoin => {
+                        self.link.send_self(Msg::WebRequest(bridge::client::Message::QuickJoin {
+                            criteria: MatchCriteria::default(),
+                        }));
+                        false
+                    }
+                    RoomEvent::CancelQuickJoin => {
+                        self.link.send_self(Msg::WebRequest(bridge::client::Message::CancelQuickJoin));
                         false
                     }
+                    RoomEvent::StartGame => {
+                        self.link.send_self(Msg::WebRequest(bridge::client::Message::StartGame));
+                        false
+                    }
+                    RoomEvent::GameAction(action) => {
+                        self.link.send_self(Msg::WebRequest(bridge::client::Message::GameAction { action }));
+                        false
+                    }
+                    RoomEvent::AddBot => {
+                        self.link.send_self(Msg::WebRequest(bridge::client::Message::AddBot));
+                        false
+                    }
+                    RoomEvent::RemoveBot(bot) => {
+                        self.link.send_self(Msg::WebRequest(bridge::client::Message::RemoveBot { bot }));
+                        false
+                    }
+                    RoomEvent::ChooseTeam(team) => {
+                        self.link.send_self(Msg::WebRequest(bridge::client::Message::ChooseTeam { team }));
+                        false
+                    }
+                    RoomEvent::ShuffleTeams => {
+                        self.link.send_self(Msg::WebRequest(bridge::client::Message::ShuffleTeams));
+                        false
+                    }
+                    RoomEvent::ClearTeams => {
+                        self.link.send_self(Msg::WebRequest(bridge::client::Message::ClearTeams));
+                        false
+                    }
+                    RoomEvent::Query(query) => {
+                        self.link.send_self(Msg::WebRequest(bridge::client::Message::QueryRooms { query }));
+                        false
+                    }
+                    RoomEvent::Hover(room) => {
+                        let client = self.client.as_mut().unwrap();
+                        client.hovered = room;
+                        if let Some(room) = room {
+                            self.link.send_self(Msg::WebRequest(bridge::client::Message::GetRoomDetail { room }));
+                            false
+                        } else {
+                            client.preview = None;
+                            true
+                        }
+                    }
                 }
             }
             Msg::WebRequest(message) => {
@@ -141,11 +626,14 @@ impl Component for Model {
             Msg::WebResponse(Ok(message)) => {
                 use bridge::server::Message;
                 match message {
-                    Message::Welcome { id, users, rooms } => {
+                    Message::Welcome { id, resume, users, rooms, games } => {
+                        self.session_storage.store(RESUME_KEY, Json(&resume));
+                        self.resume = Some(resume);
                         let user_list = users.iter().fold(HashMap::new(), |mut result, user| {
                             result.insert(user.id, user.clone());
                             result
                         });
+                        let RoomPage { query, rooms, total } = rooms;
                         let room_list = rooms.iter().fold(HashMap::new(), |mut result, room| {
                             result.insert(room.id, room.clone());
                             result
@@ -155,8 +643,27 @@ impl Component for Model {
                             users: user_list,
                             chats: vec![],
                             rooms: room_list,
+                            query,
+                            room_total: total,
                             room: None,
+                            hovered: None,
+                            preview: None,
+                            matching: None,
+                            invites: vec![],
+                            friends: HashMap::new(),
+                            requests: vec![],
+                            blocked: HashSet::new(),
+                            game: None,
+                            timer: None,
+                            clock_offset: 0,
+                            leaderboard_game: GameType::Dice,
+                            leaderboard: vec![],
+                            matches: vec![],
+                            tournaments: BTreeMap::new(),
+                            games,
                         });
+                        self.link.send_self(Msg::LoadLeaderboard(GameType::Dice));
+                        self.link.send_self(Msg::LoadMatches);
                         true
                     }
                     Message::Connected { user } => {
@@ -169,6 +676,20 @@ impl Component for Model {
                         client.users.remove(&id);
                         true
                     }
+                    // 곧 연결이 닫힌다
+                    Message::Disconnecting { reason } => {
+                        self.disconnect = Some(reason);
+                        false
+                    }
+                    // 접속이 거절되면 입장 전에 온다
+                    Message::Alert(Alert::Sanctioned { kind, reason, until }) if self.client.is_none() => {
+                        self.login = Some(Some(sanction_text(kind, &reason, until)));
+                        true
+                    }
+                    Message::Alert(Alert::Maintenance { seconds }) if self.client.is_none() => {
+                        self.login = Some(Some(maintenance_text(seconds)));
+                        true
+                    }
                     Message::Alert(alert) => {
                         let client = self.client.as_mut().unwrap();
                         client.chats.push(Chat::Alert(
@@ -182,20 +703,82 @@ impl Component for Model {
                                 Alert::Quit { user } => {
                                     format!("{}님이 퇴장하셨습니다.", client.users.get(&user).map_or("(정보 없음)".into(), |user| user.name.clone()))
                                 }
+                                Alert::Spectate { user } => {
+                                    format!("{}님이 관전을 시작하셨습니다.", client.users.get(&user).map_or("(정보 없음)".into(), |user| user.name.clone()))
+                                }
+                                Alert::RoomFull => {
+                                    "방이 가득 찼습니다.".into()
+                                }
+                                Alert::WrongPassword => {
+                                    "비밀번호가 맞지 않습니다.".into()
+                                }
+                                Alert::InviteSent { user } => {
+                                    format!("{}님을 초대했습니다.", client.users.get(&user).map_or("(정보 없음)".into(), |user| user.name.clone()))
+                                }
+                                Alert::InviteUnavailable => {
+                                    "로비에 있는 사용자만 초대할 수 있습니다.".into()
+                                }
+                                Alert::InviteDeclined { user } => {
+                                    format!("{}님이 초대를 거절하셨습니다.", client.users.get(&user).map_or("(정보 없음)".into(), |user| user.name.clone()))
+                                }
+                                Alert::InviteExpired { user } => {
+                                    format!("{}님에게 보낸 초대가 만료되었습니다.", client.users.get(&user).map_or("(정보 없음)".into(), |user| user.name.clone()))
+                                }
+                                Alert::AccountRequired => {
+                                    "친구 기능은 회원만 사용할 수 있습니다.".into()
+                                }
+                                Alert::FriendRequestSent { user } => {
+                                    format!("{}님에게 친구 요청을 보냈습니다.", client.users.get(&user).map_or("(정보 없음)".into(), |user| user.name.clone()))
+                                }
+                                Alert::ReportReceived => {
+                                    "신고가 접수되었습니다.".into()
+                                }
+                                Alert::Sanctioned { kind, reason, until } => {
+                                    sanction_text(kind, &reason, until)
+                                }
+                                Alert::Maintenance { seconds } => {
+                                    maintenance_text(seconds)
+                                }
+                                Alert::NotEnoughPlayers => {
+                                    "인원이 부족해 게임을 시작할 수 없습니다.".into()
+                                }
+                                Alert::GameInProgress => {
+                                    "게임이 진행 중인 방입니다.".into()
+                                }
+                                Alert::InvalidMove => {
+                                    "지금은 할 수 없는 행동입니다.".into()
+                                }
+                                Alert::GameUnavailable => {
+                                    "지금은 이 게임을 시작할 수 없습니다.".into()
+                                }
+                                Alert::RatingOutOfRange { range } => {
+                                    format!("레이팅 {}~{}의 회원만 입장할 수 있습니다.", range.min, range.max)
+                                }
+                                Alert::RateLimited => {
+                                    "메시지를 너무 빨리 보내고 있습니다.".into()
+                                }
+                                Alert::TournamentFull => {
+                                    "참가 인원이 다 찬 대회입니다.".into()
+                                }
+                                Alert::TooManyTournaments => {
+                                    "더 이상 대회를 만들 수 없습니다.".into()
+                                }
                             },
                         ));
                         true
                     }
                     Message::Chat {
+                        id,
                         from,
                         text,
-                        whisper,
+                        scope,
                     } => {
                         let client = self.client.as_mut().unwrap();
-                        client.chats.push(if whisper {
-                            Chat::Whisper(text, from)
-                        } else {
-                            Chat::Chat(text, from)
+                        client.chats.push(match scope {
+                            ChatScope::Public => Chat::Chat(text, from, id),
+                            ChatScope::Whisper => Chat::Whisper(text, from, id),
+                            ChatScope::Spectator => Chat::Spectator(text, from, id),
+                            ChatScope::Team => Chat::Team(text, from, id),
                         });
                         true
                     }
@@ -204,21 +787,212 @@ impl Component for Model {
                         room
                     } => {
                         let client = self.client.as_mut().unwrap();
-                        client.rooms.insert(room.id, room);
+                        if client.query.matches(&room) {
+                            client.room_total += 1;
+                            if client.rooms.len() < ROOM_PAGE_SIZE {
+                                client.rooms.insert(room.id, room);
+                            }
+                        }
                         client.room.is_none()
                     }
                     Message::DestroyRoom {
                         room
                     } => {
                         let client = self.client.as_mut().unwrap();
-                        client.rooms.remove(&room);
+                        // 관전 중이던 방이 사라짐
+                        if client.room.as_ref().map_or(false, |current| current.id == room) {
+                            client.room = None;
+                            client.game = None;
+                            client.timer = None;
+                        }
+                        if client.rooms.remove(&room).is_some() {
+                            client.room_total = client.room_total.saturating_sub(1);
+                        }
+                        if client.hovered == Some(room) {
+                            client.hovered = None;
+                            client.preview = None;
+                        }
                         client.room.is_none()
                     }
+                    Message::RoomChanged {
+                        room
+                    } => {
+                        let client = self.client.as_mut().unwrap();
+                        if client.hovered == Some(room.id) {
+                            self.link.send_self(Msg::WebRequest(bridge::client::Message::GetRoomDetail { room: room.id }));
+                        }
+                        if client.rooms.contains_key(&room.id) {
+                            if client.query.matches(&room) {
+                                client.rooms.insert(room.id, room);
+                            } else {
+                                client.rooms.remove(&room.id);
+                                client.room_total = client.room_total.saturating_sub(1);
+                            }
+                        }
+                        client.room.is_none()
+                    }
+                    Message::RoomPage {
+                        page
+                    } => {
+                        let client = self.client.as_mut().unwrap();
+                        client.rooms = page.rooms.iter().map(|room| (room.id, room.clone())).collect();
+                        client.query = page.query;
+                        client.room_total = page.total;
+                        client.room.is_none()
+                    }
+                    Message::Invited {
+                        from,
+                        room
+                    } => {
+                        let client = self.client.as_mut().unwrap();
+                        client.invites.retain(|(_, invite)| invite.id != room.id);
+                        client.invites.push((from, room));
+                        true
+                    }
+                    Message::InviteExpired {
+                        room
+                    } => {
+                        let client = self.client.as_mut().unwrap();
+                        client.invites.retain(|(_, invite)| invite.id != room);
+                        true
+                    }
+                    Message::Friends {
+                        friends,
+                        requests
+                    } => {
+                        let client = self.client.as_mut().unwrap();
+                        client.friends = friends.into_iter().map(|friend| (friend.user.id, friend)).collect();
+                        client.requests = requests;
+                        true
+                    }
+                    Message::FriendRequested {
+                        from
+                    } => {
+                        let client = self.client.as_mut().unwrap();
+                        client.chats.push(Chat::Alert(format!("{}님이 친구 요청을 보냈습니다.", from.name)));
+                        client.requests.retain(|request| request.id != from.id);
+                        client.requests.push(from);
+                        true
+                    }
+                    Message::FriendChanged {
+                        friend
+                    } => {
+                        let client = self.client.as_mut().unwrap();
+                        client.requests.retain(|request| request.id != friend.user.id);
+                        client.friends.insert(friend.user.id, friend);
+                        true
+                    }
+                    Message::FriendRemoved {
+                        id
+                    } => {
+                        let client = self.client.as_mut().unwrap();
+                        client.friends.remove(&id);
+                        true
+                    }
+                    Message::BlockList {
+                        users
+                    } => {
+                        let client = self.client.as_mut().unwrap();
+                        client.blocked = users.into_iter().collect();
+                        true
+                    }
+                    Message::Matchmaking {
+                        status
+                    } => {
+                        let client = self.client.as_mut().unwrap();
+                        client.matching = match status {
+                            MatchStatus::Queued { estimated_wait } => Some(estimated_wait),
+                            MatchStatus::Matched { .. } | MatchStatus::Cancelled => None,
+                        };
+                        true
+                    }
                     Message::ReadyJoin {
                         room
                     } => {
                         let client = self.client.as_mut().unwrap();
                         client.room = Some(room);
+                        client.game = None;
+                        client.timer = None;
+                        self.playback = None;
+                        true
+                    }
+                    Message::Timer {
+                        deadline,
+                        now
+                    } => {
+                        let client = self.client.as_mut().unwrap();
+                        client.timer = deadline;
+                        client.clock_offset = now as i64 - Date::now() as i64;
+                        true
+                    }
+                    Message::Tournaments {
+                        tournaments
+                    } => {
+                        let client = self.client.as_mut().unwrap();
+                        client.tournaments = tournaments
+                            .into_iter()
+                            .map(|tournament| (tournament.id, tournament))
+                            .collect();
+                        true
+                    }
+                    Message::TournamentChanged {
+                        tournament
+                    } => {
+                        let client = self.client.as_mut().unwrap();
+                        client.tournaments.insert(tournament.id, tournament);
+                        true
+                    }
+                    Message::TournamentClosed {
+                        id
+                    } => {
+                        let client = self.client.as_mut().unwrap();
+                        client.tournaments.remove(&id);
+                        true
+                    }
+                    Message::GameUpdate {
+                        view
+                    } => {
+                        let client = self.client.as_mut().unwrap();
+                        client.game = Some(view);
+                        true
+                    }
+                    Message::GameEvent {
+                        event
+                    } => {
+                        let client = self.client.as_mut().unwrap();
+                        let playback = &self.playback;
+                        let name_of = |user: &UserId| {
+                            let replayed = || playback.as_ref()?.record.players.iter().find(|player| player.id == *user);
+                            client.users.get(user).map(|user| user.name.clone())
+                                .or_else(|| replayed().map(|player| player.name.clone()))
+                                .unwrap_or_else(|| "(정보 없음)".into())
+                        };
+                        let text = match event {
+                            GameEvent::WordAccepted { player, word } => format!("{}: {}", name_of(&player), word),
+                            GameEvent::WordRejected { player, word, reason } => {
+                                format!("{}: {} ({})", name_of(&player), word, rejection_text(reason))
+                            }
+                            GameEvent::Eliminated { player } => format!("{}님이 탈락했습니다.", name_of(&player)),
+                        };
+                        client.chats.push(Chat::Alert(text));
+                        true
+                    }
+                    Message::GameResult {
+                        result
+                    } => {
+                        let client = self.client.as_mut().unwrap();
+                        let winners = result.players
+                            .iter()
+                            .filter(|player| result.won(player.id))
+                            .map(|player| player.name.clone())
+                            .collect::<Vec<_>>();
+                        client.chats.push(Chat::Alert(if winners.is_empty() {
+                            "승자 없이 게임이 끝났습니다.".into()
+                        } else {
+                            format!("{}님이 이겼습니다.", winners.join(", "))
+                        }));
+                        self.menu = Some(MenuItem::Result(result));
+                        self.link.send_self(Msg::LoadMatches);
                         true
                     }
                     Message::RoomUpdate {
@@ -228,7 +1002,15 @@ impl Component for Model {
                         client.room = Some(room);
                         true
                     }
-                    Message::RoomDetail { .. } => unimplemented!(),
+                    Message::RoomDetail {
+                        room
+                    } => {
+                        let client = self.client.as_mut().unwrap();
+                        if client.hovered == Some(room.id) {
+                            client.preview = Some(room);
+                        }
+                        client.room.is_none()
+                    }
                 }
             }
             Msg::WebResponse(Err(_)) => false,
@@ -241,11 +1023,19 @@ impl Renderable<Model> for Model {
         let body = if let Some(client) = &self.client {
             let users = &client.users;
             let chats = client.chats.clone().into_boxed_slice();
+            let tournaments = client.tournaments.values().cloned().collect::<Vec<_>>();
+            let team_of = |id: UserId| {
+                client.room
+                    .as_ref()
+                    .and_then(|room| room.teams.iter().find(|(user, _)| *user == id))
+                    .map(|&(_, team)| team)
+            };
+            let my_team = team_of(client.id);
             let main = if let Some(room) = &client.room {
-                let mut members = room.members
-                    .iter()
-                    .filter_map(|user| client.users.get(&user))
-                    .map(|user| html! {
+                let member = room.members.contains(&client.id);
+                let view_member = |user: &UserBrief| {
+                    let id = user.id;
+                    html! {
                         <li>
                             <a href="#",>
                                 <header>{ &user.name }</header>
@@ -256,34 +1046,191 @@ impl Renderable<Model> for Model {
                                         html! {}
                                     }
                                 }
+                                {
+                                    if user.bot {
+                                        html! { <i class="bot",>{ "봇" }</i> }
+                                    } else {
+                                        html! {}
+                                    }
+                                }
                             </a>
+                            {
+                                if user.bot && room.owner == client.id && !room.playing {
+                                    html! { <a href="#", onclick=|_| Msg::RoomEvent(RoomEvent::RemoveBot(id)),>{ "내보내기" }</a> }
+                                } else {
+                                    html! {}
+                                }
+                            }
                         </li>
+                    }
+                };
+                // 팀이 있으면 팀별로 묶어 보여준다
+                let sides = if room.teams.is_empty() {
+                    vec![None]
+                } else {
+                    vec![Some(Some(Team::Red)), Some(Some(Team::Blue)), Some(None)]
+                };
+                let mut groups = sides.into_iter().map(|side| {
+                    let mut members = room.members
+                        .iter()
+                        .filter(|&&id| side.map_or(true, |team| team_of(id) == team))
+                        .filter_map(|user| client.users.get(&user))
+                        .map(|user| view_member(user));
+                    html! {
+                        <>
+                            {
+                                match side {
+                                    Some(Some(team)) => html! { <header>{ team_name(team) }</header> },
+                                    Some(None) => html! { <header>{ "미정" }</header> },
+                                    None => html! {},
+                                }
+                            }
+                            <ul>
+                                { for members }
+                            </ul>
+                        </>
+                    }
+                });
+                let mut spectators = room.spectators
+                    .iter()
+                    .filter_map(|user| client.users.get(&user))
+                    .map(|user| html! {
+                        <li><a href="#",><header>{ &user.name }</header></a></li>
                     });
                 html! {
                     <>
                         <section id="menu",>
                             <ul>
                                 <li><a href="#", onclick=|_| Msg::RoomEvent(RoomEvent::Quit)>{ "나가기" }</a></li>
+                                {
+                                    if room.owner == client.id && !room.playing {
+                                        html! {
+                                            <>
+                                                <li><a href="#", onclick=|_| Msg::RoomEvent(RoomEvent::StartGame)>{ "게임 시작" }</a></li>
+                                                <li><a href="#", onclick=|_| Msg::RoomEvent(RoomEvent::AddBot)>{ "봇 추가" }</a></li>
+                                                <li><a href="#", onclick=|_| Msg::RoomEvent(RoomEvent::ShuffleTeams)>{ "팀 섞기" }</a></li>
+                                                <li><a href="#", onclick=|_| Msg::RoomEvent(RoomEvent::ClearTeams)>{ "팀 해제" }</a></li>
+                                            </>
+                                        }
+                                    } else {
+                                        html! {}
+                                    }
+                                }
+                                {
+                                    if member && !room.playing {
+                                        html! {
+                                            <>
+                                                <li><a href="#", onclick=|_| Msg::RoomEvent(RoomEvent::ChooseTeam(Some(Team::Red)))>{ "홍팀으로" }</a></li>
+                                                <li><a href="#", onclick=|_| Msg::RoomEvent(RoomEvent::ChooseTeam(Some(Team::Blue)))>{ "청팀으로" }</a></li>
+                                                <li><a href="#", onclick=|_| Msg::RoomEvent(RoomEvent::ChooseTeam(None))>{ "팀 빠지기" }</a></li>
+                                            </>
+                                        }
+                                    } else {
+                                        html! {}
+                                    }
+                                }
                             </ul>
                         </section>
+                        <section id="game-board",>
+                            <header>{ game_name(room.game) }</header>
+                            <CountdownBar: deadline=client.timer,
+                                           offset=client.clock_offset,/>
+                            <GameBoard: view=&client.game,
+                                        users=users,
+                                        me=client.id,
+                                        onaction=|action| Msg::RoomEvent(RoomEvent::GameAction(action)),/>
+                        </section>
                         <section id="room-members",>
+                            { for groups }
+                        </section>
+                        <section id="room-spectators",>
+                            <header>{ "관전자" }</header>
                             <ul>
-                                { for members }
+                                { for spectators }
                             </ul>
                         </section>
                     </>
                 }
+            } else if let Some(playback) = &self.playback {
+                let mut players = users.clone();
+                for player in playback.record.players.iter() {
+                    players.entry(player.id).or_insert_with(|| UserBrief {
+                        id: player.id,
+                        name: player.name.clone(),
+                        bot: false,
+                    });
+                }
+                html! {
+                    <>
+                        <section id="menu",>
+                            <ul>
+                                <li><a href="#", onclick=|_| Msg::StopReplay>{ "그만 보기" }</a></li>
+                            </ul>
+                        </section>
+                        <section id="game-board",>
+                            <header>{ format!("{} 다시 보기 #{}", game_name(playback.record.game), playback.record.id) }</header>
+                            <CountdownBar: deadline=client.timer,
+                                           offset=client.clock_offset,/>
+                            <GameBoard: view=&client.game,
+                                        users=players,
+                                        me=client.id,/>
+                        </section>
+                    </>
+                }
             } else {
                 html! {
                     <>
                         <section id="menu",>
                             <ul>
                                 <li><a href="#", onclick=|_| Msg::OpenMenu(MenuItem::CreateRoom)>{ "방 만들기" }</a></li>
+                                <li><a href="#", onclick=|_| Msg::OpenMenu(MenuItem::CreateTournament)>{ "대회 만들기" }</a></li>
+                                {
+                                    if let Some(wait) = client.matching {
+                                        html! {
+                                            <li>
+                                                <span>{ format!("매칭 중 (예상 {}초)", wait) }</span>
+                                                <a href="#", onclick=|_| Msg::RoomEvent(RoomEvent::CancelQuickJoin)>{ "취소" }</a>
+                                            </li>
+                                        }
+                                    } else {
+                                        html! {
+                                            <li><a href="#", onclick=|_| Msg::RoomEvent(RoomEvent::QuickJoin)>{ "빠른 입장" }</a></li>
+                                        }
+                                    }
+                                }
                             </ul>
                         </section>
                         <section id="room-list",>
-                            <RoomList: rooms=&client.rooms, onclick=|room_id| Msg::RoomEvent(RoomEvent::Join(room_id)),/>
+                            <RoomList: rooms=&client.rooms,
+                                       query=&client.query,
+                                       total=client.room_total,
+                                       onquery=|query| Msg::RoomEvent(RoomEvent::Query(query)),
+                                       onspectate=|room_id| Msg::RoomEvent(RoomEvent::Spectate(room_id)),
+                                       onclick=|room_id| Msg::RoomEvent(RoomEvent::Join(room_id)),
+                                       onhover=|room_id| Msg::RoomEvent(RoomEvent::Hover(room_id)),/>
                         </section>
+                        <aside id="room-preview",>
+                            <RoomDetail: room=&client.preview, users=users,/>
+                        </aside>
+                        <aside id="leaderboard",>
+                            <Leaderboard: game=client.leaderboard_game,
+                                          entries=&client.leaderboard,
+                                          onselect=|game| Msg::LoadLeaderboard(game),/>
+                        </aside>
+                        <aside id="tournaments",>
+                            <TournamentList: me=client.id,
+                                             tournaments=&tournaments,
+                                             onjoin=|id| Msg::TournamentEvent(TournamentEvent::Join(id)),
+                                             onleave=|id| Msg::TournamentEvent(TournamentEvent::Leave(id)),
+                                             onstart=|id| Msg::TournamentEvent(TournamentEvent::Start(id)),
+                                             oncancel=|id| Msg::TournamentEvent(TournamentEvent::Cancel(id)),
+                                             onview=|id| Msg::OpenMenu(MenuItem::Bracket(id)),/>
+                        </aside>
+                        <aside id="match-history",>
+                            <MatchHistory: me=client.id,
+                                           matches=&client.matches,
+                                           onreplay=|id| Msg::WatchReplay(id),/>
+                        </aside>
                     </>
                 }
             };
@@ -291,7 +1238,15 @@ impl Renderable<Model> for Model {
                 <>
                 <section>
                     <aside id="user-list">
-                        <UserList: users=users,/>
+                        <UserList: users=users,
+                                   friends=&client.friends,
+                                   requests=&client.requests,
+                                   blocked=&client.blocked,
+                                   onclick=|user| Msg::UserClicked(user),
+                                   onfriend=|user| Msg::FriendEvent(FriendEvent::Request(user)),
+                                   onaccept=|user| Msg::FriendEvent(FriendEvent::Accept(user)),
+                                   onremove=|user| Msg::FriendEvent(FriendEvent::Remove(user)),
+                                   onblock=|(user, block)| Msg::FriendEvent(FriendEvent::Block(user, block)),/>
                     </aside>
                     <main>{ main }</main>
                 </section>
@@ -299,7 +1254,9 @@ impl Renderable<Model> for Model {
                     <aside>
                     </aside>
                     <article id="chat-box",>
-                        <ChatBox: chats=chats, mapper=users, onsubmit=|text| Msg::SendChat(text),/>
+                        <ChatBox: chats=chats, mapper=users, team=my_team.is_some(),
+                                 onsubmit=|(text, team)| Msg::SendChat(text, team),
+                                 onreport=|(user, chat)| Msg::OpenMenu(MenuItem::Report(user, chat)),/>
                     </article>
                 </footer>
                 </>
@@ -312,12 +1269,62 @@ impl Renderable<Model> for Model {
                 <section id="game-area",>{ body }</section>
                 <section id="modal-area",>
                 {
-                    if let Some(item) = &self.menu {
+                    if let Some(error) = &self.login {
+                        html! {
+                            <LoginModal: error=error,
+                                         onlogin=|credentials| Msg::LogIn(credentials, false),
+                                         onregister=|credentials| Msg::LogIn(credentials, true),
+                                         onguest=|_| Msg::Connect, />
+                        }
+                    } else if let Some(item) = &self.menu {
                         match item {
                             MenuItem::CreateRoom => html! {
-                                <CreateRoomModal: onsubmit=|room| Msg::MenuEvent(MenuEvent::CreateRoom(room)),
+                                <CreateRoomModal: games=self.client.as_ref().map_or(vec![], |client| client.games.clone()),
+                                                  onsubmit=|(room, password)| Msg::MenuEvent(MenuEvent::CreateRoom(room, password)),
+                                                  oncancel=|_| Msg::MenuEvent(MenuEvent::Cancel), />
+                            },
+                            MenuItem::Report(user, chat) => {
+                                let (user, chat) = (*user, *chat);
+                                html! {
+                                    <ReportModal: onsubmit=|reason| Msg::MenuEvent(MenuEvent::Report(user, chat, reason)),
                                                   oncancel=|_| Msg::MenuEvent(MenuEvent::Cancel), />
+                                }
                             }
+                            MenuItem::JoinLocked(room) => {
+                                let room = *room;
+                                html! {
+                                    <PasswordModal: onsubmit=|password| Msg::MenuEvent(MenuEvent::JoinLocked(room, password)),
+                                                    oncancel=|_| Msg::MenuEvent(MenuEvent::Cancel), />
+                                }
+                            }
+                            MenuItem::CreateTournament => html! {
+                                <CreateTournamentModal: games=self.client.as_ref().map_or(vec![], |client| client.games.clone()),
+                                                        onsubmit=|tournament| Msg::MenuEvent(MenuEvent::CreateTournament(tournament)),
+                                                        oncancel=|_| Msg::MenuEvent(MenuEvent::Cancel), />
+                            },
+                            MenuItem::Bracket(id) => {
+                                let tournament = self.client.as_ref().and_then(|client| client.tournaments.get(id)).cloned();
+                                html! {
+                                    <dialog open=true, class="bracket",>
+                                        <BracketView: tournament=tournament,/>
+                                        <fieldset>
+                                            <input type="button", value="닫기", onclick=|_| Msg::MenuEvent(MenuEvent::Cancel),/>
+                                        </fieldset>
+                                    </dialog>
+                                }
+                            }
+                            MenuItem::Result(result) => html! {
+                                <ResultModal: result=result,
+                                              onclose=|_| Msg::MenuEvent(MenuEvent::Cancel), />
+                            },
+                        }
+                    } else if let Some((from, room)) = self.client.as_ref().and_then(|client| client.invites.first()) {
+                        let client = self.client.as_ref().unwrap();
+                        let from = client.users.get(from).map_or("(정보 없음)".into(), |user| user.name.clone());
+                        html! {
+                            <InviteModal: from=from, room=room,
+                                          onaccept=|room| Msg::MenuEvent(MenuEvent::AcceptInvite(room)),
+                                          ondecline=|room| Msg::MenuEvent(MenuEvent::DeclineInvite(room)), />
                         }
                     } else {
                         html! {}
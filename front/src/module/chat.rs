@@ -1,9 +1,11 @@
-use bridge::{UserBrief, UserId};
+use bridge::{ChatId, UserBrief, UserId};
 use yew::prelude::*;
 
 pub enum Msg {
     GotInput(String),
     Submit,
+    ToggleTeam,
+    Report(UserId, ChatId),
 }
 
 use std::collections::HashMap;
@@ -12,7 +14,10 @@ use std::collections::HashMap;
 pub struct ChatBoxProps {
     pub chats: Box<[Chat]>,
     pub mapper: HashMap<UserId, UserBrief>,
-    pub onsubmit: Option<Callback<(String)>>,
+    /// 팀에 속해 있어야 팀 채팅을 할 수 있다
+    pub team: bool,
+    pub onsubmit: Option<Callback<(String, bool)>>,
+    pub onreport: Option<Callback<(UserId, ChatId)>>,
 }
 
 impl Default for ChatBoxProps {
@@ -20,7 +25,9 @@ impl Default for ChatBoxProps {
         ChatBoxProps {
             chats: vec![].into_boxed_slice(),
             mapper: HashMap::new(),
+            team: false,
             onsubmit: None,
+            onreport: None,
         }
     }
 }
@@ -28,14 +35,19 @@ impl Default for ChatBoxProps {
 #[derive(Clone, PartialEq)]
 pub enum Chat {
     Alert(String),
-    Chat(String, UserId),
-    Whisper(String, UserId),
+    Chat(String, UserId, ChatId),
+    Whisper(String, UserId, ChatId),
+    Spectator(String, UserId, ChatId),
+    Team(String, UserId, ChatId),
 }
 
 pub struct ChatBox {
     chats: Box<[Chat]>,
     mapper: HashMap<UserId, UserBrief>,
-    onsubmit: Option<Callback<(String)>>,
+    team: bool,
+    to_team: bool,
+    onsubmit: Option<Callback<(String, bool)>>,
+    onreport: Option<Callback<(UserId, ChatId)>>,
     input: String,
 }
 
@@ -47,7 +59,10 @@ impl Component for ChatBox {
         Self {
             chats: props.chats,
             onsubmit: props.onsubmit,
+            onreport: props.onreport,
             mapper: props.mapper,
+            team: props.team,
+            to_team: false,
             input: String::new(),
         }
     }
@@ -62,19 +77,31 @@ impl Component for ChatBox {
                 if let Some(callback) = &self.onsubmit {
                     let text = self.input.clone();
                     if text.trim().len() > 0 {
-                        callback.emit(text);
+                        callback.emit((text, self.team && self.to_team));
                     }
                 }
                 self.input.clear();
                 false
             }
+            Msg::ToggleTeam => {
+                self.to_team = !self.to_team;
+                true
+            }
+            Msg::Report(user, chat) => {
+                if let Some(onreport) = &self.onreport {
+                    onreport.emit((user, chat));
+                }
+                false
+            }
         }
     }
 
     fn change(&mut self, props: Self::Properties) -> ShouldRender {
         self.chats = props.chats;
         self.mapper = props.mapper;
+        self.team = props.team;
         self.onsubmit = props.onsubmit;
+        self.onreport = props.onreport;
         true
     }
 }
@@ -82,16 +109,29 @@ impl Component for ChatBox {
 impl Renderable<ChatBox> for ChatBox {
     fn view(&self) -> Html<Self> {
         let mut chat_list = self.chats.iter().map(|chat| {
-            let (header, main) = match chat {
-                Chat::Alert(text) => ("알림".into(), text),
-                Chat::Chat(text, user) | Chat::Whisper(text, user) => {
-                    (self.mapper.get(&user).unwrap().name.clone(), text)
+            let (header, main, source) = match chat {
+                Chat::Alert(text) => ("알림".into(), text, None),
+                Chat::Chat(text, user, id) | Chat::Whisper(text, user, id) => {
+                    (self.mapper.get(&user).unwrap().name.clone(), text, Some((*user, *id)))
+                }
+                Chat::Spectator(text, user, id) => {
+                    (format!("[관전] {}", self.mapper.get(&user).unwrap().name), text, Some((*user, *id)))
+                }
+                Chat::Team(text, user, id) => {
+                    (format!("[팀] {}", self.mapper.get(&user).unwrap().name), text, Some((*user, *id)))
                 }
             };
             html! {
                 <li class="chat-item",>
                     <header>{ header }</header>
                     <main>{ main }</main>
+                    {
+                        if let Some((user, id)) = source {
+                            html! { <a href="#", class="report", onclick=|_| Msg::Report(user, id),>{ "신고" }</a> }
+                        } else {
+                            html! {}
+                        }
+                    }
                 </li>
             }
         });
@@ -99,6 +139,18 @@ impl Renderable<ChatBox> for ChatBox {
             <>
                 <ul id="chat-list",>{ for chat_list }</ul>
                 <form id="chat-input", action="javascript:void(0)", onsubmit=|_| Msg::Submit,>
+                    {
+                        if self.team {
+                            html! {
+                                <>
+                                    <label for="team-chat",>{ "팀" }</label>
+                                    <input type="checkbox", name="team-chat", checked=self.to_team, onclick=|_| Msg::ToggleTeam,/>
+                                </>
+                            }
+                        } else {
+                            html! {}
+                        }
+                    }
                     <input type="text", value=self.input, oninput=|e| Msg::GotInput(e.value),/>
                     <input type="submit", value="전송"/>
                 </form>
@@ -0,0 +1,108 @@
+use bridge::{Deadline, TimerKind};
+use std::time::Duration;
+use stdweb::web::Date;
+use yew::prelude::*;
+use yew::services::interval::{IntervalService, IntervalTask};
+
+const TICK: Duration = Duration::from_millis(100);
+
+#[derive(Clone, PartialEq)]
+pub struct CountdownBarProps {
+    pub deadline: Option<Deadline>,
+    /// Server clock minus local clock, in milliseconds.
+    pub offset: i64,
+}
+
+pub enum Msg {
+    Tick,
+}
+
+pub struct CountdownBar {
+    deadline: Option<Deadline>,
+    offset: i64,
+    interval: IntervalService,
+    ticking: Option<IntervalTask>,
+    link: ComponentLink<Self>,
+}
+
+impl Default for CountdownBarProps {
+    fn default() -> Self {
+        Self {
+            deadline: None,
+            offset: 0,
+        }
+    }
+}
+
+impl Component for CountdownBar {
+    type Message = Msg;
+    type Properties = CountdownBarProps;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let mut bar = Self {
+            deadline: None,
+            offset: 0,
+            interval: IntervalService::new(),
+            ticking: None,
+            link,
+        };
+        bar.change(props);
+        bar
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::Tick => {
+                if self.left() == 0 {
+                    self.ticking = None;
+                }
+                true
+            }
+        }
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        self.deadline = props.deadline;
+        self.offset = props.offset;
+        self.ticking = match self.deadline {
+            Some(_) => {
+                let tick = self.link.send_back(|_| Msg::Tick);
+                Some(self.interval.spawn(TICK, tick))
+            }
+            None => None,
+        };
+        true
+    }
+}
+
+impl CountdownBar {
+    /// Milliseconds until the deadline by the server's clock.
+    fn left(&self) -> u64 {
+        let now = (Date::now() as i64 + self.offset).max(0) as u64;
+        self.deadline
+            .map_or(0, |deadline| deadline.end.saturating_sub(now))
+    }
+}
+
+impl Renderable<CountdownBar> for CountdownBar {
+    fn view(&self) -> Html<Self> {
+        let deadline = match self.deadline {
+            Some(deadline) => deadline,
+            None => return html! {},
+        };
+        let label = match deadline.kind {
+            TimerKind::Countdown => "시작까지",
+            TimerKind::Turn => "남은 시간",
+            TimerKind::Interval => "다음 판까지",
+        };
+        let left = self.left();
+        let total = deadline.end.saturating_sub(deadline.start).max(1);
+        let width = format!("width: {}%", left.min(total) * 100 / total);
+        html! {
+            <div class="countdown",>
+                <header>{ format!("{} {}초", label, (left + 999) / 1000) }</header>
+                <div class="countdown-bar",><div style=width,></div></div>
+            </div>
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use bridge::*;
+use std::collections::HashMap;
+use yew::prelude::*;
+
+pub fn game_name(kind: GameKind) -> &'static str {
+    match kind {
+        GameKind::Dice => "주사위",
+        GameKind::WordChain { .. } => "끝말잇기",
+    }
+}
+
+pub fn team_name(team: Team) -> &'static str {
+    match team {
+        Team::Red => "홍팀",
+        Team::Blue => "청팀",
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub struct GameBoardProps {
+    pub view: Option<GameView>,
+    pub users: HashMap<UserId, UserBrief>,
+    pub me: UserId,
+    pub onaction: Option<Callback<GameAction>>,
+}
+
+pub enum Msg {
+    Action(GameAction),
+    GotWord(String),
+    SubmitWord,
+}
+
+pub struct GameBoard {
+    view: Option<GameView>,
+    users: HashMap<UserId, UserBrief>,
+    me: UserId,
+    onaction: Option<Callback<GameAction>>,
+    word: String,
+}
+
+impl Default for GameBoardProps {
+    fn default() -> Self {
+        Self {
+            view: None,
+            users: HashMap::new(),
+            me: 0,
+            onaction: None,
+        }
+    }
+}
+
+impl Component for GameBoard {
+    type Message = Msg;
+    type Properties = GameBoardProps;
+
+    fn create(props: Self::Properties, _link: ComponentLink<Self>) -> Self {
+        Self {
+            view: props.view,
+            users: props.users,
+            me: props.me,
+            onaction: props.onaction,
+            word: String::new(),
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::Action(action) => {
+                if let Some(onaction) = &self.onaction {
+                    onaction.emit(action);
+                }
+                false
+            }
+            Msg::GotWord(word) => {
+                self.word = word;
+                true
+            }
+            Msg::SubmitWord => {
+                let word = self.word.trim().to_string();
+                self.word.clear();
+                if word.len() > 0 {
+                    self.update(Msg::Action(GameAction::Word { word }));
+                }
+                true
+            }
+        }
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        self.view = props.view;
+        self.users = props.users;
+        self.me = props.me;
+        self.onaction = props.onaction;
+        true
+    }
+}
+
+impl GameBoard {
+    fn name_of(&self, user: UserId) -> String {
+        self.users
+            .get(&user)
+            .map_or("(정보 없음)".into(), |user| user.name.clone())
+    }
+
+    fn view_dice(&self, rolls: &[(UserId, Option<u8>)]) -> Html<Self> {
+        let mut list = rolls.iter().map(|&(user, rolled)| {
+            html! {
+                <li>
+                    <header>{ self.name_of(user) }</header>
+                    <span>{ rolled.map_or("?".into(), |rolled| rolled.to_string()) }</span>
+                </li>
+            }
+        });
+        let can_roll = self.onaction.is_some()
+            && rolls.iter().any(|&(user, rolled)| user == self.me && rolled.is_none());
+        html! {
+            <>
+                <ul class="dice",>{ for list }</ul>
+                {
+                    if can_roll {
+                        html! { <a href="#", onclick=|_| Msg::Action(GameAction::Roll),>{ "굴리기" }</a> }
+                    } else {
+                        html! {}
+                    }
+                }
+            </>
+        }
+    }
+
+    fn view_word_chain(&self, players: &[(UserId, bool)], turn: Option<UserId>, word: &str, starts: &[char]) -> Html<Self> {
+        let mut list = players.iter().map(|&(user, alive)| {
+            let class = if turn == Some(user) {
+                "turn"
+            } else if alive {
+                ""
+            } else {
+                "out"
+            };
+            html! {
+                <li class=class,>{ self.name_of(user) }</li>
+            }
+        });
+        let starts = starts.iter().map(char::to_string).collect::<Vec<_>>().join(" / ");
+        html! {
+            <>
+                <ul class="word-chain-players",>{ for list }</ul>
+                <h1>{ word }</h1>
+                <p>{ format!("{}(으)로 시작하는 단어", starts) }</p>
+                {
+                    if turn == Some(self.me) && self.onaction.is_some() {
+                        html! {
+                            <form action="javascript:void(0)", onsubmit=|_| Msg::SubmitWord,>
+                                <input type="text", value=self.word, oninput=|e| Msg::GotWord(e.value),/>
+                                <input type="submit", value="입력",/>
+                            </form>
+                        }
+                    } else {
+                        html! {}
+                    }
+                }
+            </>
+        }
+    }
+}
+
+impl Renderable<GameBoard> for GameBoard {
+    fn view(&self) -> Html<Self> {
+        match &self.view {
+            Some(GameView::Dice { rolls }) => self.view_dice(rolls),
+            Some(GameView::WordChain { players, turn, word, starts, .. }) => self.view_word_chain(players, *turn, word, starts),
+            None => html! {},
+        }
+    }
+}
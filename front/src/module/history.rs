@@ -0,0 +1,87 @@
+use super::game_name;
+use bridge::*;
+use yew::prelude::*;
+
+#[derive(Clone, PartialEq)]
+pub struct MatchHistoryProps {
+    pub me: UserId,
+    pub matches: Vec<MatchRecord>,
+    pub onreplay: Option<Callback<MatchId>>,
+}
+
+pub enum Msg {
+    Replay(MatchId),
+}
+
+pub struct MatchHistory {
+    me: UserId,
+    matches: Vec<MatchRecord>,
+    onreplay: Option<Callback<MatchId>>,
+}
+
+impl Default for MatchHistoryProps {
+    fn default() -> Self {
+        Self {
+            me: 0,
+            matches: vec![],
+            onreplay: None,
+        }
+    }
+}
+
+impl Component for MatchHistory {
+    type Message = Msg;
+    type Properties = MatchHistoryProps;
+
+    fn create(props: Self::Properties, _link: ComponentLink<Self>) -> Self {
+        Self {
+            me: props.me,
+            matches: props.matches,
+            onreplay: props.onreplay,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::Replay(id) => {
+                if let Some(onreplay) = &self.onreplay {
+                    onreplay.emit(id);
+                }
+                false
+            }
+        }
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        self.me = props.me;
+        self.matches = props.matches;
+        self.onreplay = props.onreplay;
+        true
+    }
+}
+
+impl Renderable<MatchHistory> for MatchHistory {
+    fn view(&self) -> Html<Self> {
+        let mut list = self.matches.iter().map(|record| {
+            let id = record.id;
+            let score = record.players
+                .iter()
+                .find(|player| player.id == self.me)
+                .map_or(0, |player| player.score);
+            let won = if record.won(self.me) { "승리" } else { "패배" };
+            html! {
+                <li>
+                    <header>{ game_name(record.game) }</header>
+                    <span>{ format!("{} ({}점)", won, score) }</span>
+                    <a href="#", onclick=|_| Msg::Replay(id),>{ "다시 보기" }</a>
+                </li>
+            }
+        });
+        html! {
+            <>
+                <header>{ "최근 전적" }</header>
+                <ul>{ for list }</ul>
+            </>
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use super::game_name;
+use bridge::api::LeaderboardEntry;
+use bridge::*;
+use yew::prelude::*;
+
+#[derive(Clone, PartialEq)]
+pub struct LeaderboardProps {
+    pub game: GameType,
+    pub entries: Vec<LeaderboardEntry>,
+    pub onselect: Option<Callback<GameType>>,
+}
+
+pub enum Msg {
+    Select(GameType),
+}
+
+pub struct Leaderboard {
+    game: GameType,
+    entries: Vec<LeaderboardEntry>,
+    onselect: Option<Callback<GameType>>,
+}
+
+impl Default for LeaderboardProps {
+    fn default() -> Self {
+        Self {
+            game: GameType::Dice,
+            entries: vec![],
+            onselect: None,
+        }
+    }
+}
+
+impl Component for Leaderboard {
+    type Message = Msg;
+    type Properties = LeaderboardProps;
+
+    fn create(props: Self::Properties, _link: ComponentLink<Self>) -> Self {
+        Self {
+            game: props.game,
+            entries: props.entries,
+            onselect: props.onselect,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::Select(game) => {
+                if let Some(onselect) = &self.onselect {
+                    onselect.emit(game);
+                }
+                false
+            }
+        }
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        self.game = props.game;
+        self.entries = props.entries;
+        self.onselect = props.onselect;
+        true
+    }
+}
+
+impl Renderable<Leaderboard> for Leaderboard {
+    fn view(&self) -> Html<Self> {
+        let games = [
+            GameKind::Dice,
+            GameKind::WordChain { initial_sound_rule: true, turn_seconds: 15 },
+        ];
+        let mut tabs = games.iter().map(|&game| {
+            let game_type = game.game_type();
+            let class = if game_type == self.game { "selected" } else { "" };
+            html! {
+                <li><a href="#", class=class, onclick=|_| Msg::Select(game_type),>{ game_name(game) }</a></li>
+            }
+        });
+        let mut rows = self.entries.iter().enumerate().map(|(rank, entry)| {
+            html! {
+                <tr>
+                    <td>{ rank + 1 }</td>
+                    <td>{ &entry.name }</td>
+                    <td>{ entry.rating }</td>
+                    <td>{ entry.games }</td>
+                </tr>
+            }
+        });
+        html! {
+            <>
+                <header>{ "순위" }</header>
+                <ul class="tab",>{ for tabs }</ul>
+                <table>
+                    <tr><th>{ "#" }</th><th>{ "이름" }</th><th>{ "레이팅" }</th><th>{ "판 수" }</th></tr>
+                    { for rows }
+                </table>
+            </>
+        }
+    }
+}
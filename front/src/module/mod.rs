@@ -7,5 +7,23 @@ pub use userlist::{UserList, UserListProps};
 mod roomlist;
 pub use roomlist::{RoomList, RoomListProps};
 
+mod roomdetail;
+pub use roomdetail::{RoomDetail, RoomDetailProps};
+
+mod countdown;
+pub use countdown::{CountdownBar, CountdownBarProps};
+
+mod game;
+pub use game::{game_name, team_name, GameBoard, GameBoardProps};
+
+mod history;
+pub use history::{MatchHistory, MatchHistoryProps};
+
+mod leaderboard;
+pub use leaderboard::{Leaderboard, LeaderboardProps};
+
+mod tournament;
+pub use tournament::{BracketView, BracketViewProps, TournamentList, TournamentListProps};
+
 mod modal;
 pub use modal::*;
\ No newline at end of file
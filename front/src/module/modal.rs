@@ -1,23 +1,56 @@
 use yew::prelude::*;
 use bridge::*;
+use super::{game_name, team_name};
 
 mod create_room {
     use super::*;
 
     #[derive(Clone, PartialEq, Default)]
     pub struct CreateRoomModalProps {
-        pub onsubmit: Option<Callback<RoomBrief>>,
+        /// Games the server hosts.
+        pub games: Vec<GameType>,
+        pub onsubmit: Option<Callback<(RoomBrief, Option<String>)>>,
         pub oncancel: Option<Callback<()>>,
     }
     pub struct CreateRoomModal {
         title: String,
-        onsubmit: Option<Callback<RoomBrief>>,
+        capacity: String,
+        separate_spectators: bool,
+        password: String,
+        game: GameKind,
+        initial_sound_rule: bool,
+        turn_seconds: String,
+        rated: bool,
+        min_rating: String,
+        max_rating: String,
+        games: Vec<GameType>,
+        onsubmit: Option<Callback<(RoomBrief, Option<String>)>>,
         oncancel: Option<Callback<()>>,
     }
     pub enum Msg {
         Submit,
         Cancelled,
         GotInput(String),
+        GotCapacity(String),
+        ToggleSpectators,
+        GotPassword(String),
+        Game(GameKind),
+        ToggleInitialSound,
+        GotTurnSeconds(String),
+        ToggleRated,
+        GotMinRating(String),
+        GotMaxRating(String),
+    }
+
+    /// Fills the options of the chosen game in from the form.
+    fn with_options(game: GameKind, initial_sound_rule: bool, turn_seconds: &str) -> GameKind {
+        match game {
+            GameKind::WordChain { .. } => GameKind::WordChain {
+                initial_sound_rule,
+                turn_seconds: turn_seconds.trim().parse().unwrap_or(15),
+            },
+            game => game,
+        }
     }
 
     impl Component for CreateRoomModal {
@@ -27,6 +60,16 @@ mod create_room {
         fn create(props: Self::Properties, _link: ComponentLink<Self>) -> Self {
             Self {
                 title: String::new(),
+                capacity: "8".into(),
+                separate_spectators: false,
+                password: String::new(),
+                game: GameKind::default(),
+                initial_sound_rule: true,
+                turn_seconds: "15".into(),
+                rated: false,
+                min_rating: "1400".into(),
+                max_rating: "1600".into(),
+                games: props.games,
                 onsubmit: props.onsubmit,
                 oncancel: props.oncancel,
             }
@@ -37,11 +80,26 @@ mod create_room {
                 Msg::Submit => {
                     if let Some(onsubmit) = &self.onsubmit {
                         let title = self.title.trim().to_string();
+                        let capacity = self.capacity.trim().parse().unwrap_or(0);
+                        let password = Some(self.password.trim().to_string())
+                            .filter(|password| password.len() > 0);
+                        let rating = match (self.min_rating.trim().parse(), self.max_rating.trim().parse()) {
+                            (Ok(min), Ok(max)) if self.rated => Some(RatingRange { min, max }),
+                            _ => None,
+                        };
                         if title.len() > 0 {
-                            onsubmit.emit(RoomBrief {
+                            onsubmit.emit((RoomBrief {
                                 id: 0,
                                 title,
-                            });
+                                owner: 0,
+                                members: 0,
+                                capacity,
+                                playing: false,
+                                separate_spectators: self.separate_spectators,
+                                locked: password.is_some(),
+                                game: with_options(self.game, self.initial_sound_rule, &self.turn_seconds),
+                                rating,
+                            }, password));
                         }
                     }
                     false
@@ -56,17 +114,231 @@ mod create_room {
                     self.title = new_title;
                     true
                 }
+                Msg::GotCapacity(new_capacity) => {
+                    self.capacity = new_capacity;
+                    true
+                }
+                Msg::ToggleSpectators => {
+                    self.separate_spectators = !self.separate_spectators;
+                    true
+                }
+                Msg::GotPassword(new_password) => {
+                    self.password = new_password;
+                    true
+                }
+                Msg::Game(game) => {
+                    self.game = game;
+                    true
+                }
+                Msg::ToggleInitialSound => {
+                    self.initial_sound_rule = !self.initial_sound_rule;
+                    true
+                }
+                Msg::GotTurnSeconds(new_seconds) => {
+                    self.turn_seconds = new_seconds;
+                    true
+                }
+                Msg::ToggleRated => {
+                    self.rated = !self.rated;
+                    true
+                }
+                Msg::GotMinRating(new_rating) => {
+                    self.min_rating = new_rating;
+                    true
+                }
+                Msg::GotMaxRating(new_rating) => {
+                    self.max_rating = new_rating;
+                    true
+                }
             }
         }
     }
 
     impl Renderable<CreateRoomModal> for CreateRoomModal {
         fn view(&self) -> Html<Self> {
+            let games = [
+                GameKind::Dice,
+                GameKind::WordChain { initial_sound_rule: true, turn_seconds: 15 },
+            ];
+            let mut game_list = games.iter().filter(|game| self.games.contains(&game.game_type())).map(|&game| {
+                let selected = std::mem::discriminant(&game) == std::mem::discriminant(&self.game);
+                let class = if selected { "selected" } else { "" };
+                html! {
+                    <li><a href="#", class=class, onclick=|_| Msg::Game(game),>{ game_name(game) }</a></li>
+                }
+            });
             html! {
                 <dialog open=true>
                     <form action="javascript:void(0)", onsubmit=|_| Msg::Submit,>
                         <label for="title",>{ "방 제목" }</label>
                         <input type="text", name="title", value=self.title, oninput=|e| Msg::GotInput(e.value),/>
+                        <label>{ "게임" }</label>
+                        <ul class="game-kind",>{ for game_list }</ul>
+                        {
+                            if let GameKind::WordChain { .. } = self.game {
+                                html! {
+                                    <>
+                                        <label for="initial-sound",>{ "두음법칙 허용" }</label>
+                                        <input type="checkbox", name="initial-sound", checked=self.initial_sound_rule, onclick=|_| Msg::ToggleInitialSound,/>
+                                        <label for="turn-seconds",>{ "제한 시간 (초)" }</label>
+                                        <input type="number", name="turn-seconds", min="5", max="60", value=self.turn_seconds, oninput=|e| Msg::GotTurnSeconds(e.value),/>
+                                    </>
+                                }
+                            } else {
+                                html! {}
+                            }
+                        }
+                        <label for="capacity",>{ "최대 인원" }</label>
+                        <input type="number", name="capacity", min="2", max="8", value=self.capacity, oninput=|e| Msg::GotCapacity(e.value),/>
+                        <label for="separate-spectators",>{ "관전자 채팅 분리" }</label>
+                        <input type="checkbox", name="separate-spectators", checked=self.separate_spectators, onclick=|_| Msg::ToggleSpectators,/>
+                        <label for="rated",>{ "레이팅 제한" }</label>
+                        <input type="checkbox", name="rated", checked=self.rated, onclick=|_| Msg::ToggleRated,/>
+                        {
+                            if self.rated {
+                                html! {
+                                    <>
+                                        <input type="number", name="min-rating", value=self.min_rating, oninput=|e| Msg::GotMinRating(e.value),/>
+                                        <span>{ "~" }</span>
+                                        <input type="number", name="max-rating", value=self.max_rating, oninput=|e| Msg::GotMaxRating(e.value),/>
+                                    </>
+                                }
+                            } else {
+                                html! {}
+                            }
+                        }
+                        <label for="password",>{ "비밀번호 (선택)" }</label>
+                        <input type="password", name="password", value=self.password, oninput=|e| Msg::GotPassword(e.value),/>
+                        <fieldset>
+                            <input type="button", value="취소", onclick=|_| Msg::Cancelled,/>
+                            <input type="submit", value="확인",/>
+                        </fieldset>
+                    </form>
+                </dialog>
+            }
+        }
+    }
+}
+
+pub use create_room::{CreateRoomModal, CreateRoomModalProps};
+
+mod create_tournament {
+    use super::*;
+
+    #[derive(Clone, PartialEq, Default)]
+    pub struct CreateTournamentModalProps {
+        /// Games the server hosts.
+        pub games: Vec<GameType>,
+        pub onsubmit: Option<Callback<NewTournament>>,
+        pub oncancel: Option<Callback<()>>,
+    }
+    pub struct CreateTournamentModal {
+        name: String,
+        game: GameKind,
+        elimination: Elimination,
+        capacity: String,
+        games: Vec<GameType>,
+        onsubmit: Option<Callback<NewTournament>>,
+        oncancel: Option<Callback<()>>,
+    }
+    pub enum Msg {
+        Submit,
+        Cancelled,
+        GotName(String),
+        Game(GameKind),
+        Elimination(Elimination),
+        GotCapacity(String),
+    }
+
+    impl Component for CreateTournamentModal {
+        type Message = Msg;
+        type Properties = CreateTournamentModalProps;
+
+        fn create(props: Self::Properties, _link: ComponentLink<Self>) -> Self {
+            Self {
+                name: String::new(),
+                game: GameKind::default(),
+                elimination: Elimination::Single,
+                capacity: "8".into(),
+                games: props.games,
+                onsubmit: props.onsubmit,
+                oncancel: props.oncancel,
+            }
+        }
+
+        fn update(&mut self, msg: Self::Message) -> ShouldRender {
+            match msg {
+                Msg::Submit => {
+                    if let Some(onsubmit) = &self.onsubmit {
+                        let name = self.name.trim().to_string();
+                        if name.len() > 0 {
+                            onsubmit.emit(NewTournament {
+                                name,
+                                game: self.game,
+                                elimination: self.elimination,
+                                capacity: self.capacity.trim().parse().unwrap_or(0),
+                            });
+                        }
+                    }
+                    false
+                }
+                Msg::Cancelled => {
+                    if let Some(oncancel) = &self.oncancel {
+                        oncancel.emit(());
+                    }
+                    false
+                }
+                Msg::GotName(new_name) => {
+                    self.name = new_name;
+                    true
+                }
+                Msg::Game(game) => {
+                    self.game = game;
+                    true
+                }
+                Msg::Elimination(elimination) => {
+                    self.elimination = elimination;
+                    true
+                }
+                Msg::GotCapacity(new_capacity) => {
+                    self.capacity = new_capacity;
+                    true
+                }
+            }
+        }
+    }
+
+    impl Renderable<CreateTournamentModal> for CreateTournamentModal {
+        fn view(&self) -> Html<Self> {
+            let games = [
+                GameKind::Dice,
+                GameKind::WordChain { initial_sound_rule: true, turn_seconds: 15 },
+            ];
+            let mut game_list = games.iter().filter(|game| self.games.contains(&game.game_type())).map(|&game| {
+                let selected = std::mem::discriminant(&game) == std::mem::discriminant(&self.game);
+                let class = if selected { "selected" } else { "" };
+                html! {
+                    <li><a href="#", class=class, onclick=|_| Msg::Game(game),>{ game_name(game) }</a></li>
+                }
+            });
+            let eliminations = [(Elimination::Single, "싱글 엘리미네이션"), (Elimination::Double, "더블 엘리미네이션")];
+            let mut elimination_list = eliminations.iter().map(|&(elimination, name)| {
+                let class = if elimination == self.elimination { "selected" } else { "" };
+                html! {
+                    <li><a href="#", class=class, onclick=|_| Msg::Elimination(elimination),>{ name }</a></li>
+                }
+            });
+            html! {
+                <dialog open=true>
+                    <form action="javascript:void(0)", onsubmit=|_| Msg::Submit,>
+                        <label for="name",>{ "대회 이름" }</label>
+                        <input type="text", name="name", value=self.name, oninput=|e| Msg::GotName(e.value),/>
+                        <label>{ "게임" }</label>
+                        <ul class="game-kind",>{ for game_list }</ul>
+                        <label>{ "방식" }</label>
+                        <ul class="elimination",>{ for elimination_list }</ul>
+                        <label for="capacity",>{ "최대 인원" }</label>
+                        <input type="number", name="capacity", min="2", max="32", value=self.capacity, oninput=|e| Msg::GotCapacity(e.value),/>
                         <fieldset>
                             <input type="button", value="취소", onclick=|_| Msg::Cancelled,/>
                             <input type="submit", value="확인",/>
@@ -78,4 +350,462 @@ mod create_room {
     }
 }
 
-pub use create_room::{CreateRoomModal, CreateRoomModalProps};
\ No newline at end of file
+pub use create_tournament::{CreateTournamentModal, CreateTournamentModalProps};
+
+mod password {
+    use super::*;
+
+    #[derive(Clone, PartialEq, Default)]
+    pub struct PasswordModalProps {
+        pub onsubmit: Option<Callback<String>>,
+        pub oncancel: Option<Callback<()>>,
+    }
+    pub struct PasswordModal {
+        password: String,
+        onsubmit: Option<Callback<String>>,
+        oncancel: Option<Callback<()>>,
+    }
+    pub enum Msg {
+        Submit,
+        Cancelled,
+        GotInput(String),
+    }
+
+    impl Component for PasswordModal {
+        type Message = Msg;
+        type Properties = PasswordModalProps;
+
+        fn create(props: Self::Properties, _link: ComponentLink<Self>) -> Self {
+            Self {
+                password: String::new(),
+                onsubmit: props.onsubmit,
+                oncancel: props.oncancel,
+            }
+        }
+
+        fn update(&mut self, msg: Self::Message) -> ShouldRender {
+            match msg {
+                Msg::Submit => {
+                    if let Some(onsubmit) = &self.onsubmit {
+                        onsubmit.emit(self.password.trim().to_string());
+                    }
+                    false
+                }
+                Msg::Cancelled => {
+                    if let Some(oncancel) = &self.oncancel {
+                        oncancel.emit(());
+                    }
+                    false
+                }
+                Msg::GotInput(new_password) => {
+                    self.password = new_password;
+                    true
+                }
+            }
+        }
+    }
+
+    impl Renderable<PasswordModal> for PasswordModal {
+        fn view(&self) -> Html<Self> {
+            html! {
+                <dialog open=true>
+                    <form action="javascript:void(0)", onsubmit=|_| Msg::Submit,>
+                        <label for="password",>{ "비밀번호" }</label>
+                        <input type="password", name="password", value=self.password, oninput=|e| Msg::GotInput(e.value),/>
+                        <fieldset>
+                            <input type="button", value="취소", onclick=|_| Msg::Cancelled,/>
+                            <input type="submit", value="입장",/>
+                        </fieldset>
+                    </form>
+                </dialog>
+            }
+        }
+    }
+}
+
+pub use password::{PasswordModal, PasswordModalProps};
+
+mod invite {
+    use super::*;
+
+    #[derive(Clone, PartialEq)]
+    pub struct InviteModalProps {
+        pub from: String,
+        pub room: RoomBrief,
+        pub onaccept: Option<Callback<RoomId>>,
+        pub ondecline: Option<Callback<RoomId>>,
+    }
+    pub struct InviteModal {
+        from: String,
+        room: RoomBrief,
+        onaccept: Option<Callback<RoomId>>,
+        ondecline: Option<Callback<RoomId>>,
+    }
+    pub enum Msg {
+        Accepted,
+        Declined,
+    }
+
+    impl Default for InviteModalProps {
+        fn default() -> Self {
+            Self {
+                from: String::new(),
+                room: RoomBrief {
+                    id: 0,
+                    title: String::new(),
+                    owner: 0,
+                    members: 0,
+                    capacity: 0,
+                    playing: false,
+                    separate_spectators: false,
+                    locked: false,
+                    game: GameKind::default(),
+                    rating: None,
+                },
+                onaccept: None,
+                ondecline: None,
+            }
+        }
+    }
+
+    impl Component for InviteModal {
+        type Message = Msg;
+        type Properties = InviteModalProps;
+
+        fn create(props: Self::Properties, _link: ComponentLink<Self>) -> Self {
+            Self {
+                from: props.from,
+                room: props.room,
+                onaccept: props.onaccept,
+                ondecline: props.ondecline,
+            }
+        }
+
+        fn update(&mut self, msg: Self::Message) -> ShouldRender {
+            let callback = match msg {
+                Msg::Accepted => &self.onaccept,
+                Msg::Declined => &self.ondecline,
+            };
+            if let Some(callback) = callback {
+                callback.emit(self.room.id);
+            }
+            false
+        }
+
+        fn change(&mut self, props: Self::Properties) -> ShouldRender {
+            self.from = props.from;
+            self.room = props.room;
+            self.onaccept = props.onaccept;
+            self.ondecline = props.ondecline;
+            true
+        }
+    }
+
+    impl Renderable<InviteModal> for InviteModal {
+        fn view(&self) -> Html<Self> {
+            html! {
+                <dialog open=true>
+                    <p>{ format!("{}님이 #{} {} 방으로 초대했습니다.", self.from, self.room.id, self.room.title) }</p>
+                    <fieldset>
+                        <input type="button", value="거절", onclick=|_| Msg::Declined,/>
+                        <input type="button", value="수락", onclick=|_| Msg::Accepted,/>
+                    </fieldset>
+                </dialog>
+            }
+        }
+    }
+}
+
+pub use invite::{InviteModal, InviteModalProps};
+
+mod login {
+    use super::*;
+    use bridge::api::Credentials;
+
+    #[derive(Clone, PartialEq, Default)]
+    pub struct LoginModalProps {
+        pub error: Option<String>,
+        pub onlogin: Option<Callback<Credentials>>,
+        pub onregister: Option<Callback<Credentials>>,
+        pub onguest: Option<Callback<()>>,
+    }
+    pub struct LoginModal {
+        name: String,
+        password: String,
+        error: Option<String>,
+        onlogin: Option<Callback<Credentials>>,
+        onregister: Option<Callback<Credentials>>,
+        onguest: Option<Callback<()>>,
+    }
+    pub enum Msg {
+        Login,
+        Register,
+        Guest,
+        GotName(String),
+        GotPassword(String),
+    }
+
+    impl LoginModal {
+        fn credentials(&self) -> Credentials {
+            Credentials {
+                name: self.name.trim().to_string(),
+                password: self.password.clone(),
+            }
+        }
+    }
+
+    impl Component for LoginModal {
+        type Message = Msg;
+        type Properties = LoginModalProps;
+
+        fn create(props: Self::Properties, _link: ComponentLink<Self>) -> Self {
+            Self {
+                name: String::new(),
+                password: String::new(),
+                error: props.error,
+                onlogin: props.onlogin,
+                onregister: props.onregister,
+                onguest: props.onguest,
+            }
+        }
+
+        fn update(&mut self, msg: Self::Message) -> ShouldRender {
+            match msg {
+                Msg::Login => {
+                    if let Some(onlogin) = &self.onlogin {
+                        onlogin.emit(self.credentials());
+                    }
+                    false
+                }
+                Msg::Register => {
+                    if let Some(onregister) = &self.onregister {
+                        onregister.emit(self.credentials());
+                    }
+                    false
+                }
+                Msg::Guest => {
+                    if let Some(onguest) = &self.onguest {
+                        onguest.emit(());
+                    }
+                    false
+                }
+                Msg::GotName(new_name) => {
+                    self.name = new_name;
+                    true
+                }
+                Msg::GotPassword(new_password) => {
+                    self.password = new_password;
+                    true
+                }
+            }
+        }
+
+        fn change(&mut self, props: Self::Properties) -> ShouldRender {
+            self.error = props.error;
+            self.onlogin = props.onlogin;
+            self.onregister = props.onregister;
+            self.onguest = props.onguest;
+            true
+        }
+    }
+
+    impl Renderable<LoginModal> for LoginModal {
+        fn view(&self) -> Html<Self> {
+            html! {
+                <dialog open=true>
+                    <form action="javascript:void(0)", onsubmit=|_| Msg::Login,>
+                        <label for="name",>{ "이름" }</label>
+                        <input type="text", name="name", value=self.name, oninput=|e| Msg::GotName(e.value),/>
+                        <label for="password",>{ "비밀번호" }</label>
+                        <input type="password", name="password", value=self.password, oninput=|e| Msg::GotPassword(e.value),/>
+                        {
+                            if let Some(error) = &self.error {
+                                html! { <p class="error",>{ error }</p> }
+                            } else {
+                                html! {}
+                            }
+                        }
+                        <fieldset>
+                            <input type="button", value="손님으로 입장", onclick=|_| Msg::Guest,/>
+                            <input type="button", value="회원가입", onclick=|_| Msg::Register,/>
+                            <input type="submit", value="로그인",/>
+                        </fieldset>
+                    </form>
+                </dialog>
+            }
+        }
+    }
+}
+
+pub use login::{LoginModal, LoginModalProps};
+
+mod report {
+    use super::*;
+
+    #[derive(Clone, PartialEq, Default)]
+    pub struct ReportModalProps {
+        pub onsubmit: Option<Callback<String>>,
+        pub oncancel: Option<Callback<()>>,
+    }
+    pub struct ReportModal {
+        reason: String,
+        onsubmit: Option<Callback<String>>,
+        oncancel: Option<Callback<()>>,
+    }
+    pub enum Msg {
+        Submit,
+        Cancelled,
+        GotInput(String),
+    }
+
+    impl Component for ReportModal {
+        type Message = Msg;
+        type Properties = ReportModalProps;
+
+        fn create(props: Self::Properties, _link: ComponentLink<Self>) -> Self {
+            Self {
+                reason: String::new(),
+                onsubmit: props.onsubmit,
+                oncancel: props.oncancel,
+            }
+        }
+
+        fn update(&mut self, msg: Self::Message) -> ShouldRender {
+            match msg {
+                Msg::Submit => {
+                    if let Some(onsubmit) = &self.onsubmit {
+                        let reason = self.reason.trim().to_string();
+                        if reason.len() > 0 {
+                            onsubmit.emit(reason);
+                        }
+                    }
+                    false
+                }
+                Msg::Cancelled => {
+                    if let Some(oncancel) = &self.oncancel {
+                        oncancel.emit(());
+                    }
+                    false
+                }
+                Msg::GotInput(new_reason) => {
+                    self.reason = new_reason;
+                    true
+                }
+            }
+        }
+    }
+
+    impl Renderable<ReportModal> for ReportModal {
+        fn view(&self) -> Html<Self> {
+            html! {
+                <dialog open=true>
+                    <form action="javascript:void(0)", onsubmit=|_| Msg::Submit,>
+                        <label for="reason",>{ "신고 사유" }</label>
+                        <input type="text", name="reason", value=self.reason, oninput=|e| Msg::GotInput(e.value),/>
+                        <fieldset>
+                            <input type="button", value="취소", onclick=|_| Msg::Cancelled,/>
+                            <input type="submit", value="신고",/>
+                        </fieldset>
+                    </form>
+                </dialog>
+            }
+        }
+    }
+}
+
+pub use report::{ReportModal, ReportModalProps};
+
+mod result {
+    use super::*;
+
+    #[derive(Clone, PartialEq, Default)]
+    pub struct ResultModalProps {
+        pub result: MatchRecord,
+        pub onclose: Option<Callback<()>>,
+    }
+    pub struct ResultModal {
+        result: MatchRecord,
+        onclose: Option<Callback<()>>,
+    }
+    pub enum Msg {
+        Closed,
+    }
+
+    impl Component for ResultModal {
+        type Message = Msg;
+        type Properties = ResultModalProps;
+
+        fn create(props: Self::Properties, _link: ComponentLink<Self>) -> Self {
+            Self {
+                result: props.result,
+                onclose: props.onclose,
+            }
+        }
+
+        fn update(&mut self, msg: Self::Message) -> ShouldRender {
+            match msg {
+                Msg::Closed => {
+                    if let Some(onclose) = &self.onclose {
+                        onclose.emit(());
+                    }
+                    false
+                }
+            }
+        }
+
+        fn change(&mut self, props: Self::Properties) -> ShouldRender {
+            self.result = props.result;
+            self.onclose = props.onclose;
+            true
+        }
+    }
+
+    impl Renderable<ResultModal> for ResultModal {
+        fn view(&self) -> Html<Self> {
+            let mut players = self.result.players.clone();
+            players.sort_by(|a, b| b.score.cmp(&a.score));
+            let teams = players.iter().any(|player| player.team.is_some());
+            let mut rows = players.iter().map(|player| {
+                let class = if self.result.won(player.id) { "winner" } else { "" };
+                html! {
+                    <tr class=class,>
+                        <td>{ &player.name }</td>
+                        {
+                            if teams {
+                                html! { <td>{ player.team.map_or("", team_name) }</td> }
+                            } else {
+                                html! {}
+                            }
+                        }
+                        <td>{ player.score }</td>
+                    </tr>
+                }
+            });
+            html! {
+                <dialog open=true>
+                    <header>{ format!("{} 결과", game_name(self.result.game)) }</header>
+                    <table>
+                        <tr>
+                            <th>{ "이름" }</th>
+                            {
+                                if teams {
+                                    html! { <th>{ "팀" }</th> }
+                                } else {
+                                    html! {}
+                                }
+                            }
+                            <th>{ "점수" }</th>
+                        </tr>
+                        { for rows }
+                    </table>
+                    <p>{ format!("{}분 {}초", self.result.duration / 60, self.result.duration % 60) }</p>
+                    <fieldset>
+                        <input type="button", value="닫기", onclick=|_| Msg::Closed,/>
+                    </fieldset>
+                </dialog>
+            }
+        }
+    }
+}
+
+pub use result::{ResultModal, ResultModalProps};
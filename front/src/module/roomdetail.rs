@@ -0,0 +1,72 @@
+use bridge::*;
+use std::collections::HashMap;
+use yew::prelude::*;
+
+#[derive(Clone, PartialEq)]
+pub struct RoomDetailProps {
+    pub room: Option<Room>,
+    pub users: HashMap<UserId, UserBrief>,
+}
+
+pub struct RoomDetail {
+    room: Option<Room>,
+    users: HashMap<UserId, UserBrief>,
+}
+
+impl Default for RoomDetailProps {
+    fn default() -> Self {
+        Self {
+            room: None,
+            users: HashMap::new(),
+        }
+    }
+}
+
+impl Component for RoomDetail {
+    type Message = ();
+    type Properties = RoomDetailProps;
+
+    fn create(props: Self::Properties, _link: ComponentLink<Self>) -> Self {
+        Self {
+            room: props.room,
+            users: props.users,
+        }
+    }
+
+    fn update(&mut self, _: Self::Message) -> ShouldRender {
+        false
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        self.room = props.room;
+        self.users = props.users;
+        true
+    }
+}
+
+impl Renderable<RoomDetail> for RoomDetail {
+    fn view(&self) -> Html<Self> {
+        if let Some(room) = &self.room {
+            let name_of = |user: &UserId| {
+                self.users
+                    .get(user)
+                    .map_or("(정보 없음)".into(), |user| user.name.clone())
+            };
+            let mut members = room.members.iter().map(|user| {
+                html! {
+                    <li>{ name_of(user) }</li>
+                }
+            });
+            html! {
+                <>
+                    <header>{ format!("#{} {}", room.id, room.title) }</header>
+                    <p>{ format!("방장: {}", name_of(&room.owner)) }</p>
+                    <p>{ format!("인원: {}/{}", room.members.len(), room.capacity) }</p>
+                    <ul>{ for members }</ul>
+                </>
+            }
+        } else {
+            html! {}
+        }
+    }
+}
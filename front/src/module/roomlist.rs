@@ -1,3 +1,4 @@
+use super::game_name;
 use bridge::*;
 use std::collections::HashMap;
 use yew::prelude::*;
@@ -5,23 +6,54 @@ use yew::prelude::*;
 #[derive(Clone, PartialEq)]
 pub struct RoomListProps {
     pub rooms: HashMap<RoomId, RoomBrief>,
+    pub query: RoomQuery,
+    pub total: usize,
     pub onclick: Option<Callback<RoomId>>,
+    pub onhover: Option<Callback<Option<RoomId>>>,
+    pub onquery: Option<Callback<RoomQuery>>,
+    pub onspectate: Option<Callback<RoomId>>,
 }
 
 pub enum Msg {
     Clicked(RoomId),
+    Spectate(RoomId),
+    Hovered(Option<RoomId>),
+    GotSearch(String),
+    Search,
+    Filter(RoomFilter),
+    Sort(RoomSort),
+    Page(usize),
 }
 
 pub struct RoomList {
     rooms: HashMap<RoomId, RoomBrief>,
+    query: RoomQuery,
+    total: usize,
+    search: String,
     onclick: Option<Callback<RoomId>>,
+    onhover: Option<Callback<Option<RoomId>>>,
+    onquery: Option<Callback<RoomQuery>>,
+    onspectate: Option<Callback<RoomId>>,
 }
 
 impl Default for RoomListProps {
     fn default() -> Self {
         Self {
             rooms: HashMap::new(),
+            query: RoomQuery::default(),
+            total: 0,
             onclick: None,
+            onhover: None,
+            onquery: None,
+            onspectate: None,
+        }
+    }
+}
+
+impl RoomList {
+    fn emit_query(&self, query: RoomQuery) {
+        if let Some(onquery) = &self.onquery {
+            onquery.emit(query);
         }
     }
 }
@@ -33,7 +65,13 @@ impl Component for RoomList {
     fn create(props: Self::Properties, _link: ComponentLink<Self>) -> Self {
         Self {
             rooms: props.rooms,
+            search: props.query.title.clone(),
+            query: props.query,
+            total: props.total,
             onclick: props.onclick,
+            onhover: props.onhover,
+            onquery: props.onquery,
+            onspectate: props.onspectate,
         }
     }
 
@@ -45,31 +83,161 @@ impl Component for RoomList {
                 }
                 false
             }
+            Msg::Spectate(id) => {
+                if let Some(onspectate) = &self.onspectate {
+                    onspectate.emit(id);
+                }
+                false
+            }
+            Msg::Hovered(id) => {
+                if let Some(onhover) = &self.onhover {
+                    onhover.emit(id);
+                }
+                false
+            }
+            Msg::GotSearch(search) => {
+                self.search = search;
+                true
+            }
+            Msg::Search => {
+                self.emit_query(RoomQuery {
+                    title: self.search.trim().to_string(),
+                    page: 0,
+                    ..self.query.clone()
+                });
+                false
+            }
+            Msg::Filter(filter) => {
+                self.emit_query(RoomQuery {
+                    filter,
+                    page: 0,
+                    ..self.query.clone()
+                });
+                false
+            }
+            Msg::Sort(sort) => {
+                self.emit_query(RoomQuery {
+                    sort,
+                    page: 0,
+                    ..self.query.clone()
+                });
+                false
+            }
+            Msg::Page(page) => {
+                self.emit_query(RoomQuery {
+                    page,
+                    ..self.query.clone()
+                });
+                false
+            }
         }
     }
 
     fn change(&mut self, props: Self::Properties) -> ShouldRender {
         self.rooms = props.rooms;
+        self.query = props.query;
+        self.total = props.total;
         self.onclick = props.onclick;
+        self.onhover = props.onhover;
+        self.onquery = props.onquery;
+        self.onspectate = props.onspectate;
         true
     }
 }
 
 impl Renderable<RoomList> for RoomList {
     fn view(&self) -> Html<Self> {
-        let mut list = self.rooms.values().cloned().map(|room| {
-            let RoomBrief { id, title, .. } = room;
+        let mut rooms = self.rooms.values().collect::<Vec<_>>();
+        rooms.sort_by(|a, b| self.query.compare(a, b));
+        let mut list = rooms.into_iter().cloned().map(|room| {
+            let RoomBrief { id, title, members, capacity, playing, locked, game, rating, .. } = room;
             html! {
                 <li>
-                    <a href="#", onclick=|_| Msg::Clicked(id),>
+                    <a href="#", onclick=|_| Msg::Clicked(id),
+                                 onmouseover=|_| Msg::Hovered(Some(id)),
+                                 onmouseout=|_| Msg::Hovered(None),>
                         <header>{ &id }</header>
                         <h1>{ &title }</h1>
+                        <i>{ game_name(game) }</i>
+                        <span>{ format!("{}/{}", members, capacity) }</span>
+                        {
+                            if let Some(RatingRange { min, max }) = rating {
+                                html! { <i>{ format!("레이팅 {}~{}", min, max) }</i> }
+                            } else {
+                                html! {}
+                            }
+                        }
+                        {
+                            if locked {
+                                html! { <i>{ "비밀방" }</i> }
+                            } else {
+                                html! {}
+                            }
+                        }
+                        {
+                            if playing {
+                                html! { <i>{ "게임 중" }</i> }
+                            } else {
+                                html! {}
+                            }
+                        }
                     </a>
+                    <a href="#", class="spectate", onclick=|_| Msg::Spectate(id),>{ "관전" }</a>
                 </li>
             }
         });
+        let filters = [
+            (RoomFilter::All, "전체"),
+            (RoomFilter::Joinable, "입장 가능"),
+            (RoomFilter::Full, "꽉 찬 방"),
+            (RoomFilter::Playing, "게임 중"),
+        ];
+        let mut filter_list = filters.iter().map(|&(filter, label)| {
+            let class = if filter == self.query.filter { "selected" } else { "" };
+            html! {
+                <li><a href="#", class=class, onclick=|_| Msg::Filter(filter),>{ label }</a></li>
+            }
+        });
+        let sorts = [
+            (RoomSort::Id, "번호순"),
+            (RoomSort::Title, "제목순"),
+            (RoomSort::Members, "인원순"),
+        ];
+        let mut sort_list = sorts.iter().map(|&(sort, label)| {
+            let class = if sort == self.query.sort { "selected" } else { "" };
+            html! {
+                <li><a href="#", class=class, onclick=|_| Msg::Sort(sort),>{ label }</a></li>
+            }
+        });
+        let page = self.query.page;
+        let pages = (self.total + ROOM_PAGE_SIZE - 1) / ROOM_PAGE_SIZE;
         html! {
-            <ul>{ for list }</ul>
+            <>
+                <form id="room-search", action="javascript:void(0)", onsubmit=|_| Msg::Search,>
+                    <input type="text", placeholder="방 제목 검색", value=self.search, oninput=|e| Msg::GotSearch(e.value),/>
+                    <input type="submit", value="검색"/>
+                </form>
+                <ul class="room-filter",>{ for filter_list }</ul>
+                <ul class="room-sort",>{ for sort_list }</ul>
+                <ul>{ for list }</ul>
+                <nav class="room-page",>
+                    {
+                        if page > 0 {
+                            html! { <a href="#", onclick=|_| Msg::Page(page - 1),>{ "이전" }</a> }
+                        } else {
+                            html! {}
+                        }
+                    }
+                    <span>{ format!("{}/{}", page + 1, pages.max(1)) }</span>
+                    {
+                        if page + 1 < pages {
+                            html! { <a href="#", onclick=|_| Msg::Page(page + 1),>{ "다음" }</a> }
+                        } else {
+                            html! {}
+                        }
+                    }
+                </nav>
+            </>
         }
     }
 }
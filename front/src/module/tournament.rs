@@ -0,0 +1,300 @@
+use super::game_name;
+use bridge::*;
+use yew::prelude::*;
+
+fn state_text(tournament: &Tournament) -> String {
+    match tournament.state {
+        TournamentState::SignUp => format!("참가 신청 {}/{}", tournament.entrants.len(), tournament.capacity),
+        TournamentState::Running => "진행 중".into(),
+        TournamentState::Finished => "종료".into(),
+    }
+}
+
+fn name_of(tournament: &Tournament, user: UserId) -> String {
+    tournament.entrants
+        .iter()
+        .find(|entrant| entrant.id == user)
+        .map_or("(정보 없음)".into(), |entrant| entrant.name.clone())
+}
+
+#[derive(Clone, PartialEq)]
+pub struct TournamentListProps {
+    pub me: UserId,
+    pub tournaments: Vec<Tournament>,
+    pub onjoin: Option<Callback<TournamentId>>,
+    pub onleave: Option<Callback<TournamentId>>,
+    pub onstart: Option<Callback<TournamentId>>,
+    pub oncancel: Option<Callback<TournamentId>>,
+    pub onview: Option<Callback<TournamentId>>,
+}
+
+pub enum Msg {
+    Join(TournamentId),
+    Leave(TournamentId),
+    Start(TournamentId),
+    Cancel(TournamentId),
+    View(TournamentId),
+}
+
+pub struct TournamentList {
+    me: UserId,
+    tournaments: Vec<Tournament>,
+    onjoin: Option<Callback<TournamentId>>,
+    onleave: Option<Callback<TournamentId>>,
+    onstart: Option<Callback<TournamentId>>,
+    oncancel: Option<Callback<TournamentId>>,
+    onview: Option<Callback<TournamentId>>,
+}
+
+impl Default for TournamentListProps {
+    fn default() -> Self {
+        Self {
+            me: 0,
+            tournaments: vec![],
+            onjoin: None,
+            onleave: None,
+            onstart: None,
+            oncancel: None,
+            onview: None,
+        }
+    }
+}
+
+impl Component for TournamentList {
+    type Message = Msg;
+    type Properties = TournamentListProps;
+
+    fn create(props: Self::Properties, _link: ComponentLink<Self>) -> Self {
+        Self {
+            me: props.me,
+            tournaments: props.tournaments,
+            onjoin: props.onjoin,
+            onleave: props.onleave,
+            onstart: props.onstart,
+            oncancel: props.oncancel,
+            onview: props.onview,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        let (callback, id) = match msg {
+            Msg::Join(id) => (&self.onjoin, id),
+            Msg::Leave(id) => (&self.onleave, id),
+            Msg::Start(id) => (&self.onstart, id),
+            Msg::Cancel(id) => (&self.oncancel, id),
+            Msg::View(id) => (&self.onview, id),
+        };
+        if let Some(callback) = callback {
+            callback.emit(id);
+        }
+        false
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        self.me = props.me;
+        self.tournaments = props.tournaments;
+        self.onjoin = props.onjoin;
+        self.onleave = props.onleave;
+        self.onstart = props.onstart;
+        self.oncancel = props.oncancel;
+        self.onview = props.onview;
+        true
+    }
+}
+
+impl TournamentList {
+    fn view_actions(&self, tournament: &Tournament) -> Html<Self> {
+        let id = tournament.id;
+        let entered = tournament.entrants.iter().any(|entrant| entrant.id == self.me);
+        let organizer = tournament.organizer == self.me;
+        let signing_up = tournament.state == TournamentState::SignUp;
+        let running = tournament.state == TournamentState::Running;
+        let forfeited = tournament.forfeited.contains(&self.me);
+        html! {
+            <>
+                {
+                    if signing_up && !entered {
+                        html! { <a href="#", onclick=|_| Msg::Join(id),>{ "참가" }</a> }
+                    } else if signing_up && entered {
+                        html! { <a href="#", onclick=|_| Msg::Leave(id),>{ "참가 취소" }</a> }
+                    } else if running && entered && !forfeited {
+                        html! { <a href="#", onclick=|_| Msg::Leave(id),>{ "기권" }</a> }
+                    } else {
+                        html! {}
+                    }
+                }
+                {
+                    if organizer && signing_up {
+                        html! { <a href="#", onclick=|_| Msg::Start(id),>{ "시작" }</a> }
+                    } else {
+                        html! {}
+                    }
+                }
+                {
+                    if organizer && tournament.state != TournamentState::Finished {
+                        html! { <a href="#", onclick=|_| Msg::Cancel(id),>{ "취소" }</a> }
+                    } else {
+                        html! {}
+                    }
+                }
+                {
+                    if !signing_up {
+                        html! { <a href="#", onclick=|_| Msg::View(id),>{ "대진표" }</a> }
+                    } else {
+                        html! {}
+                    }
+                }
+            </>
+        }
+    }
+}
+
+impl Renderable<TournamentList> for TournamentList {
+    fn view(&self) -> Html<Self> {
+        let mut list = self.tournaments.iter().map(|tournament| {
+            let elimination = match tournament.elimination {
+                Elimination::Single => "싱글",
+                Elimination::Double => "더블",
+            };
+            html! {
+                <li>
+                    <header>{ &tournament.name }</header>
+                    <span>{ format!("{} · {} 엘리미네이션", game_name(tournament.game), elimination) }</span>
+                    <span>{ state_text(tournament) }</span>
+                    {
+                        if let Some(champion) = tournament.champion {
+                            html! { <span class="champion",>{ format!("우승: {}", name_of(tournament, champion)) }</span> }
+                        } else {
+                            html! {}
+                        }
+                    }
+                    { self.view_actions(tournament) }
+                </li>
+            }
+        });
+        html! {
+            <>
+                <header>{ "대회" }</header>
+                <ul>{ for list }</ul>
+            </>
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub struct BracketViewProps {
+    pub tournament: Option<Tournament>,
+}
+
+pub struct BracketView {
+    tournament: Option<Tournament>,
+}
+
+impl Default for BracketViewProps {
+    fn default() -> Self {
+        Self {
+            tournament: None,
+        }
+    }
+}
+
+impl Component for BracketView {
+    type Message = ();
+    type Properties = BracketViewProps;
+
+    fn create(props: Self::Properties, _link: ComponentLink<Self>) -> Self {
+        Self {
+            tournament: props.tournament,
+        }
+    }
+
+    fn update(&mut self, _: Self::Message) -> ShouldRender {
+        false
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        self.tournament = props.tournament;
+        true
+    }
+}
+
+impl BracketView {
+    fn view_slot(&self, tournament: &Tournament, game: &BracketMatch, slot: Slot) -> Html<Self> {
+        let (name, class) = match slot {
+            Slot::Player(user) => {
+                let class = if game.winner == Some(slot) {
+                    "winner"
+                } else if tournament.forfeited.contains(&user) {
+                    "out"
+                } else {
+                    ""
+                };
+                (name_of(tournament, user), class)
+            }
+            Slot::Bye => ("부전승".into(), "bye"),
+            Slot::Waiting => ("대기 중".into(), "waiting"),
+        };
+        html! { <li class=class,>{ name }</li> }
+    }
+
+    /// One column per round, in the order the rounds are played.
+    fn view_side(&self, tournament: &Tournament, side: BracketSide) -> Html<Self> {
+        let rounds = tournament.matches
+            .iter()
+            .filter(|game| game.side == side)
+            .map(|game| game.round)
+            .max()
+            .unwrap_or(0);
+        if rounds == 0 {
+            return html! {};
+        }
+        let mut columns = (1..=rounds).map(|round| {
+            let mut games = tournament.matches
+                .iter()
+                .filter(|game| game.side == side && game.round == round)
+                .map(|game| {
+                    html! {
+                        <ul class="bracket-match",>
+                            { self.view_slot(tournament, game, game.slots[0]) }
+                            { self.view_slot(tournament, game, game.slots[1]) }
+                        </ul>
+                    }
+                });
+            html! {
+                <li>
+                    <header>{ format!("{}라운드", round) }</header>
+                    { for games }
+                </li>
+            }
+        });
+        let title = match side {
+            BracketSide::Winners if tournament.elimination == Elimination::Double => "승자조",
+            BracketSide::Winners => "본선",
+            BracketSide::Losers => "패자조",
+            BracketSide::Final => "결승",
+        };
+        html! {
+            <section class="bracket-side",>
+                <header>{ title }</header>
+                <ol class="bracket-rounds",>{ for columns }</ol>
+            </section>
+        }
+    }
+}
+
+impl Renderable<BracketView> for BracketView {
+    fn view(&self) -> Html<Self> {
+        let tournament = match &self.tournament {
+            Some(tournament) => tournament,
+            None => return html! { <p>{ "끝난 대회입니다." }</p> },
+        };
+        html! {
+            <>
+                <header>{ &tournament.name }</header>
+                { self.view_side(tournament, BracketSide::Winners) }
+                { self.view_side(tournament, BracketSide::Losers) }
+                { self.view_side(tournament, BracketSide::Final) }
+            </>
+        }
+    }
+}
@@ -1,31 +1,72 @@
 use bridge::*;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use yew::prelude::*;
 
 #[derive(Clone, PartialEq)]
 pub struct UserListProps {
     pub users: HashMap<UserId, UserBrief>,
+    pub friends: HashMap<UserId, Friend>,
+    pub requests: Vec<UserBrief>,
+    pub blocked: HashSet<UserId>,
     pub onclick: Option<Callback<UserId>>,
+    pub onfriend: Option<Callback<UserId>>,
+    pub onaccept: Option<Callback<UserId>>,
+    pub onremove: Option<Callback<UserId>>,
+    pub onblock: Option<Callback<(UserId, bool)>>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Tab {
+    Online,
+    Friends,
 }
 
 pub enum Msg {
     Clicked(UserId),
+    Tab(Tab),
+    Friend(UserId),
+    Accept(UserId),
+    Remove(UserId),
+    Block(UserId, bool),
 }
 
 pub struct UserList {
     users: HashMap<UserId, UserBrief>,
+    friends: HashMap<UserId, Friend>,
+    requests: Vec<UserBrief>,
+    blocked: HashSet<UserId>,
+    tab: Tab,
     onclick: Option<Callback<UserId>>,
+    onfriend: Option<Callback<UserId>>,
+    onaccept: Option<Callback<UserId>>,
+    onremove: Option<Callback<UserId>>,
+    onblock: Option<Callback<(UserId, bool)>>,
 }
 
 impl Default for UserListProps {
     fn default() -> Self {
         Self {
             users: HashMap::new(),
+            friends: HashMap::new(),
+            requests: vec![],
+            blocked: HashSet::new(),
             onclick: None,
+            onfriend: None,
+            onaccept: None,
+            onremove: None,
+            onblock: None,
         }
     }
 }
 
+fn presence_text(presence: Presence) -> String {
+    match presence {
+        Presence::Offline => "오프라인".into(),
+        Presence::Lobby => "로비".into(),
+        Presence::InRoom(room) => format!("#{} 방", room),
+    }
+}
+
 impl Component for UserList {
     type Message = Msg;
     type Properties = UserListProps;
@@ -33,38 +74,133 @@ impl Component for UserList {
     fn create(props: Self::Properties, _link: ComponentLink<Self>) -> Self {
         Self {
             users: props.users,
+            friends: props.friends,
+            requests: props.requests,
+            blocked: props.blocked,
+            tab: Tab::Online,
             onclick: props.onclick,
+            onfriend: props.onfriend,
+            onaccept: props.onaccept,
+            onremove: props.onremove,
+            onblock: props.onblock,
         }
     }
 
     fn update(&mut self, msg: Self::Message) -> ShouldRender {
-        match msg {
-            Msg::Clicked(id) => {
-                if let Some(onclick) = &self.onclick {
-                    onclick.emit(id);
+        let (callback, id) = match msg {
+            Msg::Tab(tab) => {
+                self.tab = tab;
+                return true;
+            }
+            Msg::Block(id, block) => {
+                if let Some(onblock) = &self.onblock {
+                    onblock.emit((id, block));
                 }
-                false
+                return false;
             }
+            Msg::Clicked(id) => (&self.onclick, id),
+            Msg::Friend(id) => (&self.onfriend, id),
+            Msg::Accept(id) => (&self.onaccept, id),
+            Msg::Remove(id) => (&self.onremove, id),
+        };
+        if let Some(callback) = callback {
+            callback.emit(id);
         }
+        false
     }
 
     fn change(&mut self, props: Self::Properties) -> ShouldRender {
         self.users = props.users;
+        self.friends = props.friends;
+        self.requests = props.requests;
+        self.blocked = props.blocked;
         self.onclick = props.onclick;
+        self.onfriend = props.onfriend;
+        self.onaccept = props.onaccept;
+        self.onremove = props.onremove;
+        self.onblock = props.onblock;
         true
     }
 }
 
-impl Renderable<UserList> for UserList {
-    fn view(&self) -> Html<Self> {
+impl UserList {
+    fn view_online(&self) -> Html<Self> {
         let mut list = self.users.values().cloned().map(|user| {
             let UserBrief { id, name, .. } = user;
             html! {
-                <li><a href="#", onclick=|_| Msg::Clicked(id),>{ &name }</a></li>
+                <li>
+                    <a href="#", onclick=|_| Msg::Clicked(id),>{ &name }</a>
+                    {
+                        if self.friends.contains_key(&id) {
+                            html! {}
+                        } else {
+                            html! { <a href="#", class="friend", onclick=|_| Msg::Friend(id),>{ "친구 추가" }</a> }
+                        }
+                    }
+                    {
+                        if self.blocked.contains(&id) {
+                            html! { <a href="#", class="block", onclick=|_| Msg::Block(id, false),>{ "차단 해제" }</a> }
+                        } else {
+                            html! { <a href="#", class="block", onclick=|_| Msg::Block(id, true),>{ "차단" }</a> }
+                        }
+                    }
+                </li>
             }
         });
         html! {
             <ul>{ for list }</ul>
         }
     }
+
+    fn view_friends(&self) -> Html<Self> {
+        let mut requests = self.requests.iter().cloned().map(|user| {
+            let UserBrief { id, name, .. } = user;
+            html! {
+                <li>
+                    <span>{ format!("{}님의 친구 요청", name) }</span>
+                    <a href="#", onclick=|_| Msg::Accept(id),>{ "수락" }</a>
+                    <a href="#", onclick=|_| Msg::Remove(id),>{ "거절" }</a>
+                </li>
+            }
+        });
+        let mut friends = self.friends.values().cloned().map(|friend| {
+            let Friend { user: UserBrief { id, name, .. }, presence } = friend;
+            html! {
+                <li>
+                    <a href="#", onclick=|_| Msg::Clicked(id),>{ &name }</a>
+                    <i>{ presence_text(presence) }</i>
+                    <a href="#", class="friend", onclick=|_| Msg::Remove(id),>{ "삭제" }</a>
+                </li>
+            }
+        });
+        html! {
+            <>
+                <ul class="friend-requests",>{ for requests }</ul>
+                <ul>{ for friends }</ul>
+            </>
+        }
+    }
+}
+
+impl Renderable<UserList> for UserList {
+    fn view(&self) -> Html<Self> {
+        let tabs = [(Tab::Online, "접속자"), (Tab::Friends, "친구")];
+        let mut tab_list = tabs.iter().map(|&(tab, label)| {
+            let class = if tab == self.tab { "selected" } else { "" };
+            html! {
+                <li><a href="#", class=class, onclick=|_| Msg::Tab(tab),>{ label }</a></li>
+            }
+        });
+        html! {
+            <>
+                <ul class="user-tab",>{ for tab_list }</ul>
+                {
+                    match self.tab {
+                        Tab::Online => self.view_online(),
+                        Tab::Friends => self.view_friends(),
+                    }
+                }
+            </>
+        }
+    }
 }
@@ -0,0 +1,403 @@
+use crate::game::unix_time;
+use crate::storage::{self, Writer};
+use bridge::api::AuthError;
+use bridge::UserId;
+use hmac::Hmac;
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeSet, HashMap};
+use std::io;
+use subtle::ConstantTimeEq;
+
+const FILE: &str = "accounts.json";
+/// Account ids start above every possible guest id.
+const ID_BASE: UserId = 100_000_000;
+const TOKEN_LEN: usize = 32;
+const MAX_BOTS: usize = 5;
+const KDF_ROUNDS: usize = 100_000;
+/// Seconds a login token lasts. Bot tokens last until they are rotated.
+const TOKEN_LIFETIME: u64 = 30 * 24 * 60 * 60;
+/// Logging in again past this drops the oldest token.
+const MAX_TOKENS: usize = 5;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Account {
+    pub id: UserId,
+    pub name: String,
+    #[serde(flatten)]
+    secret: Secret,
+    #[serde(default)]
+    pub friends: BTreeSet<UserId>,
+    /// Pending friend requests sent to this account.
+    #[serde(default)]
+    pub requests: BTreeSet<UserId>,
+    #[serde(default)]
+    pub blocked: BTreeSet<UserId>,
+    /// Granted by editing the accounts file; there is no API for it.
+    #[serde(default)]
+    pub admin: bool,
+    /// Signs in with its token only, having no password.
+    #[serde(default)]
+    pub bot: bool,
+    /// The account that made this bot.
+    #[serde(default)]
+    pub owner: Option<UserId>,
+}
+
+/// Every account, and the login tokens that sign in to them.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct Accounts {
+    accounts: HashMap<UserId, Account>,
+    #[serde(default)]
+    logins: HashMap<String, Token>,
+    #[serde(skip)]
+    writer: Writer,
+}
+
+/// A salted password hash. Making or checking one takes a while, so that
+/// is done away from the host. The empty one of a bot matches nothing.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct Secret {
+    salt: String,
+    hash: String,
+}
+
+/// What a login is checked against, taken out to be checked elsewhere.
+pub struct Check {
+    id: UserId,
+    secret: Secret,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct Token {
+    user: UserId,
+    /// Unix time, or `None` for a bot's token.
+    expires: Option<u64>,
+}
+
+impl Token {
+    fn live(&self, now: u64) -> bool {
+        self.expires.map_or(true, |expires| expires > now)
+    }
+}
+
+fn random_string(len: usize) -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(len)
+        .collect()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Stored as `pbkdf2$<rounds>$<hex>` so the rounds can be raised later.
+fn hash(salt: &str, password: &str, rounds: usize) -> String {
+    let mut key = [0; 32];
+    pbkdf2::pbkdf2::<Hmac<Sha256>>(password.as_bytes(), salt.as_bytes(), rounds, &mut key);
+    format!("pbkdf2${}${}", rounds, hex(&key))
+}
+
+/// One round of SHA-256, which accounts made before the KDF still use
+/// until their next login.
+fn legacy_hash(salt: &str, password: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.input(salt.as_bytes());
+    hasher.input(password.as_bytes());
+    hex(&hasher.result())
+}
+
+impl Secret {
+    pub fn new(password: &str) -> Result<Self, AuthError> {
+        if password.len() < 4 {
+            return Err(AuthError::InvalidPassword);
+        }
+        Ok(Self::hashed(password))
+    }
+
+    fn hashed(password: &str) -> Self {
+        let salt = random_string(TOKEN_LEN);
+        Self {
+            hash: hash(&salt, password, KDF_ROUNDS),
+            salt,
+        }
+    }
+
+    /// `None` for a hash made before the KDF.
+    fn rounds(&self) -> Option<usize> {
+        if !self.hash.starts_with("pbkdf2$") {
+            return None;
+        }
+        self.hash
+            .split('$')
+            .nth(1)
+            .and_then(|rounds| rounds.parse().ok())
+    }
+
+    fn matches(&self, password: &str) -> bool {
+        if self.hash.is_empty() {
+            return false;
+        }
+        let expected = match self.rounds() {
+            Some(rounds) => hash(&self.salt, password, rounds),
+            None if self.hash.starts_with("pbkdf2$") => return false,
+            None => legacy_hash(&self.salt, password),
+        };
+        expected.as_bytes().ct_eq(self.hash.as_bytes()).into()
+    }
+}
+
+impl Check {
+    /// Checks `password`, and makes a stronger secret to store in place of
+    /// one from before the current rounds.
+    pub fn verify(&self, password: &str) -> Result<Option<Secret>, AuthError> {
+        if !self.secret.matches(password) {
+            return Err(AuthError::WrongCredentials);
+        }
+        Ok(match self.secret.rounds() {
+            Some(rounds) if rounds >= KDF_ROUNDS => None,
+            _ => Some(Secret::hashed(password)),
+        })
+    }
+}
+
+impl Accounts {
+    pub fn load() -> io::Result<Self> {
+        let mut accounts: Self = storage::load(FILE)?;
+        accounts.writer = Writer::start();
+        Ok(accounts)
+    }
+
+    /// Saves in the background, as logins change the tokens.
+    pub fn save(&self) {
+        self.writer.save(FILE, self.clone());
+    }
+
+    pub fn flush(&self) {
+        self.writer.flush();
+    }
+
+    pub fn get(&self, id: UserId) -> Option<&Account> {
+        self.accounts.get(&id)
+    }
+
+    pub fn get_mut(&mut self, id: UserId) -> Option<&mut Account> {
+        self.accounts.get_mut(&id)
+    }
+
+    pub fn by_token(&self, token: &str) -> Option<&Account> {
+        self.logins
+            .get(token)
+            .filter(|login| login.live(unix_time()))
+            .and_then(|login| self.accounts.get(&login.user))
+    }
+
+    pub fn admin(&self, token: &str) -> Option<&Account> {
+        self.by_token(token).filter(|account| account.admin)
+    }
+
+    fn by_name(&self, name: &str) -> Option<&Account> {
+        let name = name.to_lowercase();
+        self.accounts
+            .values()
+            .find(|account| account.name.to_lowercase() == name)
+    }
+
+    /// A bot keeps a single token, which a new one replaces. People may be
+    /// logged in from a few places at once.
+    fn issue_token(&mut self, id: UserId) -> String {
+        let now = unix_time();
+        let bot = self.accounts.get(&id).map_or(false, |account| account.bot);
+        self.logins.retain(|_, login| login.live(now));
+        let mut mine = self
+            .logins
+            .iter()
+            .filter(|(_, login)| login.user == id)
+            .map(|(token, login)| (login.expires, token.clone()))
+            .collect::<Vec<_>>();
+        mine.sort();
+        let keep = if bot { 0 } else { MAX_TOKENS - 1 };
+        let excess = mine.len().saturating_sub(keep);
+        for (_, token) in mine.into_iter().take(excess) {
+            self.logins.remove(&token);
+        }
+        let token = random_string(TOKEN_LEN);
+        let expires = if bot {
+            None
+        } else {
+            Some(now + TOKEN_LIFETIME)
+        };
+        self.logins
+            .insert(token.clone(), Token { user: id, expires });
+        self.save();
+        token
+    }
+
+    /// Whether an account may take `name`.
+    pub fn check_name(&self, name: &str) -> Result<(), AuthError> {
+        let name = name.trim();
+        let length = name.chars().count();
+        let upper = name.to_uppercase();
+        // Guests and the server's own bots are named with these.
+        if length < 2 || length > 16 || upper.starts_with("GUEST_") || upper.starts_with("BOT_") {
+            return Err(AuthError::InvalidName);
+        }
+        if self.by_name(name).is_some() {
+            return Err(AuthError::NameTaken);
+        }
+        Ok(())
+    }
+
+    fn insert(&mut self, account: Account) -> (String, &Account) {
+        let id = account.id;
+        self.accounts.insert(id, account);
+        let token = self.issue_token(id);
+        (token, &self.accounts[&id])
+    }
+
+    pub fn register(
+        &mut self,
+        name: &str,
+        secret: Secret,
+    ) -> Result<(String, &Account), AuthError> {
+        let name = name.trim();
+        self.check_name(name)?;
+        let id = ID_BASE + self.accounts.len();
+        Ok(self.insert(Account {
+            id,
+            name: name.to_string(),
+            secret,
+            friends: BTreeSet::new(),
+            requests: BTreeSet::new(),
+            blocked: BTreeSet::new(),
+            admin: false,
+            bot: false,
+            owner: None,
+        }))
+    }
+
+    /// A bot account for whoever holds `token`. Its token is only handed
+    /// out here, since an empty hash matches no password.
+    pub fn create_bot(&mut self, token: &str, name: &str) -> Result<(String, &Account), AuthError> {
+        let owner = match self.by_token(token) {
+            Some(account) if !account.bot => account.id,
+            _ => return Err(AuthError::WrongCredentials),
+        };
+        let name = name.trim();
+        self.check_name(name)?;
+        let bots = self
+            .accounts
+            .values()
+            .filter(|account| account.owner == Some(owner))
+            .count();
+        if bots >= MAX_BOTS {
+            return Err(AuthError::TooManyBots);
+        }
+        let id = ID_BASE + self.accounts.len();
+        Ok(self.insert(Account {
+            id,
+            name: name.to_string(),
+            secret: Secret::default(),
+            friends: BTreeSet::new(),
+            requests: BTreeSet::new(),
+            blocked: BTreeSet::new(),
+            admin: false,
+            bot: true,
+            owner: Some(owner),
+        }))
+    }
+
+    /// Checks that the holder of `token` owns the bot account `bot`.
+    fn owned_bot(&self, token: &str, bot: UserId) -> Result<(), AuthError> {
+        let owner = match self.by_token(token) {
+            Some(account) if !account.bot => account.id,
+            _ => return Err(AuthError::WrongCredentials),
+        };
+        match self.accounts.get(&bot) {
+            Some(account) if account.bot && account.owner == Some(owner) => Ok(()),
+            _ => Err(AuthError::WrongCredentials),
+        }
+    }
+
+    /// Gives a bot a new token in place of its old one.
+    pub fn rotate_bot(
+        &mut self,
+        token: &str,
+        bot: UserId,
+    ) -> Result<(String, &Account), AuthError> {
+        self.owned_bot(token, bot)?;
+        let token = self.issue_token(bot);
+        Ok((token, &self.accounts[&bot]))
+    }
+
+    /// Takes a bot's token away, leaving it unable to sign in until its
+    /// owner rotates it.
+    pub fn revoke_bot(&mut self, token: &str, bot: UserId) -> Result<(), AuthError> {
+        self.owned_bot(token, bot)?;
+        self.logins.retain(|_, login| login.user != bot);
+        self.save();
+        Ok(())
+    }
+
+    /// An account kept in memory only, with a token that never expires.
+    #[cfg(test)]
+    pub fn add_for_test(&mut self, name: &str) -> String {
+        let id = ID_BASE + self.accounts.len();
+        self.accounts.insert(
+            id,
+            Account {
+                id,
+                name: name.to_string(),
+                secret: Secret::default(),
+                friends: BTreeSet::new(),
+                requests: BTreeSet::new(),
+                blocked: BTreeSet::new(),
+                admin: false,
+                bot: false,
+                owner: None,
+            },
+        );
+        let token = random_string(TOKEN_LEN);
+        self.logins.insert(
+            token.clone(),
+            Token {
+                user: id,
+                expires: None,
+            },
+        );
+        token
+    }
+
+    /// What a password for `name` has to match.
+    pub fn check(&self, name: &str) -> Result<Check, AuthError> {
+        match self.by_name(name.trim()) {
+            Some(account) => Ok(Check {
+                id: account.id,
+                secret: account.secret.clone(),
+            }),
+            None => Err(AuthError::WrongCredentials),
+        }
+    }
+
+    /// Signs in with a password that passed `check`, storing the secret
+    /// made to replace a weaker one.
+    pub fn login(
+        &mut self,
+        check: Check,
+        upgrade: Option<Secret>,
+    ) -> Result<(String, &Account), AuthError> {
+        let account = match self.accounts.get_mut(&check.id) {
+            // The secret may have changed while the password was checked.
+            Some(account) if account.secret == check.secret => account,
+            _ => return Err(AuthError::WrongCredentials),
+        };
+        if let Some(secret) = upgrade {
+            account.secret = secret;
+        }
+        let token = self.issue_token(check.id);
+        Ok((token, &self.accounts[&check.id]))
+    }
+}
@@ -0,0 +1,387 @@
+use crate::account::Secret;
+use crate::game;
+use crate::replay;
+use actix::prelude::*;
+use actix_web::http::header;
+use actix_web::{error, web, Error, HttpRequest, HttpResponse};
+use bridge::api::{
+    AdminError, AuthError, Credentials, Login, Maintenance, NewBot, NewSanction, Resolve,
+};
+use bridge::{GameType, MatchId, ReportId, SanctionId, UserId};
+use futures::future::{self, Either, Future};
+use serde::Deserialize;
+
+fn respond(result: Result<Login, AuthError>) -> HttpResponse {
+    match result {
+        Ok(login) => HttpResponse::Ok().json(login),
+        Err(e) => HttpResponse::BadRequest().json(e),
+    }
+}
+
+fn peer_ip(req: &HttpRequest) -> Option<String> {
+    req.peer_addr().map(|addr| addr.ip().to_string())
+}
+
+/// Hashes on the thread pool, since the KDF would hold the host up for
+/// every other message.
+fn hashing<F, T>(hash: F) -> impl Future<Item = T, Error = Error>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    web::block(move || Ok::<_, ()>(hash())).map_err(error::ErrorInternalServerError)
+}
+
+/// Sends what `hashed` made to the host, or answers with its error.
+fn then_send<T, M>(
+    hashed: Result<T, AuthError>,
+    server: Addr<game::Host>,
+    message: impl FnOnce(T) -> M,
+) -> impl Future<Item = Result<Login, AuthError>, Error = Error>
+where
+    M: Message<Result = Result<Login, AuthError>> + Send + 'static,
+    game::Host: Handler<M>,
+{
+    match hashed {
+        Ok(hashed) => Either::A(
+            server
+                .send(message(hashed))
+                .map_err(error::ErrorInternalServerError),
+        ),
+        Err(e) => Either::B(future::ok(Err(e))),
+    }
+}
+
+pub fn register(
+    req: HttpRequest,
+    credentials: web::Json<Credentials>,
+    server: web::Data<Addr<game::Host>>,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    let Credentials { name, password } = credentials.into_inner();
+    let host = server.get_ref().clone();
+    server
+        .send(game::CheckRegister {
+            name: name.clone(),
+            ip: peer_ip(&req),
+        })
+        .map_err(error::ErrorInternalServerError)
+        .and_then(move |checked| match checked {
+            Ok(()) => Either::A(
+                hashing(move || Secret::new(&password)).and_then(move |secret| {
+                    then_send(secret, host, |secret| game::Register { name, secret })
+                }),
+            ),
+            Err(e) => Either::B(future::ok(Err(e))),
+        })
+        .map(respond)
+}
+
+pub fn login(
+    req: HttpRequest,
+    credentials: web::Json<Credentials>,
+    server: web::Data<Addr<game::Host>>,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    let Credentials { name, password } = credentials.into_inner();
+    let host = server.get_ref().clone();
+    server
+        .send(game::LogIn {
+            name,
+            ip: peer_ip(&req),
+        })
+        .map_err(error::ErrorInternalServerError)
+        .and_then(move |check| match check {
+            Ok(check) => Either::A(
+                hashing(move || {
+                    let upgrade = check.verify(&password);
+                    upgrade.map(|upgrade| (check, upgrade))
+                })
+                .and_then(move |verified| {
+                    then_send(verified, host, |(check, upgrade)| game::Verified {
+                        check,
+                        upgrade,
+                    })
+                }),
+            ),
+            Err(e) => Either::B(future::ok(Err(e))),
+        })
+        .map(respond)
+}
+
+/// Bot accounts are made by a signed-in account, which sends its own token
+/// as with admin calls. The reply carries the bot's token.
+pub fn create_bot(
+    req: HttpRequest,
+    bot: web::Json<NewBot>,
+    server: web::Data<Addr<game::Host>>,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    server
+        .send(game::CreateBot {
+            token: bearer(&req),
+            name: bot.into_inner().name,
+        })
+        .map_err(error::ErrorInternalServerError)
+        .map(|result| match result {
+            Ok(login) => HttpResponse::Ok().json(login),
+            Err(e) => bot_error(e),
+        })
+}
+
+fn bot_error(e: AuthError) -> HttpResponse {
+    match e {
+        AuthError::WrongCredentials => HttpResponse::Unauthorized().json(e),
+        e => HttpResponse::BadRequest().json(e),
+    }
+}
+
+/// Only the bot's owner may replace or take away its token.
+pub fn rotate_bot(
+    req: HttpRequest,
+    bot: web::Path<UserId>,
+    server: web::Data<Addr<game::Host>>,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    server
+        .send(game::RotateBotToken {
+            token: bearer(&req),
+            bot: bot.into_inner(),
+        })
+        .map_err(error::ErrorInternalServerError)
+        .map(|result| match result {
+            Ok(login) => HttpResponse::Ok().json(login),
+            Err(e) => bot_error(e),
+        })
+}
+
+pub fn revoke_bot(
+    req: HttpRequest,
+    bot: web::Path<UserId>,
+    server: web::Data<Addr<game::Host>>,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    server
+        .send(game::RevokeBotToken {
+            token: bearer(&req),
+            bot: bot.into_inner(),
+        })
+        .map_err(error::ErrorInternalServerError)
+        .map(|result| match result {
+            Ok(()) => HttpResponse::Ok().finish(),
+            Err(e) => bot_error(e),
+        })
+}
+
+#[derive(Deserialize)]
+pub struct MatchQuery {
+    limit: Option<usize>,
+}
+
+pub fn recent_matches(
+    user: web::Path<UserId>,
+    query: web::Query<MatchQuery>,
+    server: web::Data<Addr<game::Host>>,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    server
+        .send(game::RecentMatches {
+            user: user.into_inner(),
+            limit: query.limit.unwrap_or(10),
+        })
+        .map_err(error::ErrorInternalServerError)
+        .map(|matches| HttpResponse::Ok().json(matches))
+}
+
+pub fn replay(id: web::Path<MatchId>) -> impl Future<Item = HttpResponse, Error = Error> {
+    let id = id.into_inner();
+    web::block(move || replay::load(id))
+        .map_err(error::ErrorInternalServerError)
+        .map(|replay| match replay {
+            Some(replay) => HttpResponse::Ok().json(replay),
+            None => HttpResponse::NotFound().finish(),
+        })
+}
+
+#[derive(Deserialize)]
+pub struct LeaderboardQuery {
+    game: GameType,
+    limit: Option<usize>,
+}
+
+pub fn leaderboard(
+    query: web::Query<LeaderboardQuery>,
+    server: web::Data<Addr<game::Host>>,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    server
+        .send(game::Leaderboard {
+            game: query.game,
+            limit: query.limit.unwrap_or(20),
+        })
+        .map_err(error::ErrorInternalServerError)
+        .map(|entries| HttpResponse::Ok().json(entries))
+}
+
+#[derive(Deserialize)]
+pub struct ReportQuery {
+    all: Option<bool>,
+}
+
+/// Admin calls carry the account token as `Authorization: Bearer <token>`.
+pub fn bearer(req: &HttpRequest) -> String {
+    req.headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().splitn(2, ' ').nth(1))
+        .unwrap_or("")
+        .to_string()
+}
+
+fn admin_error(e: AdminError) -> HttpResponse {
+    match e {
+        AdminError::Unauthorized => HttpResponse::Unauthorized().json(e),
+        AdminError::NotFound => HttpResponse::NotFound().json(e),
+    }
+}
+
+fn done(result: Result<(), AdminError>) -> HttpResponse {
+    match result {
+        Ok(()) => HttpResponse::Ok().finish(),
+        Err(e) => admin_error(e),
+    }
+}
+
+pub fn list_reports(
+    req: HttpRequest,
+    query: web::Query<ReportQuery>,
+    server: web::Data<Addr<game::Host>>,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    server
+        .send(game::ListReports {
+            token: bearer(&req),
+            all: query.all.unwrap_or(false),
+        })
+        .map_err(error::ErrorInternalServerError)
+        .map(|result| match result {
+            Ok(reports) => HttpResponse::Ok().json(reports),
+            Err(e) => admin_error(e),
+        })
+}
+
+pub fn resolve_report(
+    req: HttpRequest,
+    report: web::Path<ReportId>,
+    resolve: web::Json<Resolve>,
+    server: web::Data<Addr<game::Host>>,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    server
+        .send(game::ResolveReport {
+            token: bearer(&req),
+            report: report.into_inner(),
+            resolve: resolve.into_inner(),
+        })
+        .map_err(error::ErrorInternalServerError)
+        .map(done)
+}
+
+#[derive(Deserialize)]
+pub struct SanctionQuery {
+    all: Option<bool>,
+}
+
+pub fn list_sanctions(
+    req: HttpRequest,
+    query: web::Query<SanctionQuery>,
+    server: web::Data<Addr<game::Host>>,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    server
+        .send(game::ListSanctions {
+            token: bearer(&req),
+            all: query.all.unwrap_or(false),
+        })
+        .map_err(error::ErrorInternalServerError)
+        .map(|result| match result {
+            Ok(sanctions) => HttpResponse::Ok().json(sanctions),
+            Err(e) => admin_error(e),
+        })
+}
+
+pub fn add_sanction(
+    req: HttpRequest,
+    sanction: web::Json<NewSanction>,
+    server: web::Data<Addr<game::Host>>,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    server
+        .send(game::AddSanction {
+            token: bearer(&req),
+            sanction: sanction.into_inner(),
+        })
+        .map_err(error::ErrorInternalServerError)
+        .map(|result| match result {
+            Ok(sanction) => HttpResponse::Ok().json(sanction),
+            Err(e) => admin_error(e),
+        })
+}
+
+pub fn lift_sanction(
+    req: HttpRequest,
+    sanction: web::Path<SanctionId>,
+    server: web::Data<Addr<game::Host>>,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    server
+        .send(game::LiftSanction {
+            token: bearer(&req),
+            sanction: sanction.into_inner(),
+        })
+        .map_err(error::ErrorInternalServerError)
+        .map(done)
+}
+
+pub fn kick(
+    req: HttpRequest,
+    user: web::Path<UserId>,
+    server: web::Data<Addr<game::Host>>,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    server
+        .send(game::Kick {
+            token: bearer(&req),
+            user: user.into_inner(),
+        })
+        .map_err(error::ErrorInternalServerError)
+        .map(done)
+}
+
+pub fn list_audit(
+    req: HttpRequest,
+    server: web::Data<Addr<game::Host>>,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    server
+        .send(game::ListAudit {
+            token: bearer(&req),
+        })
+        .map_err(error::ErrorInternalServerError)
+        .map(|result| match result {
+            Ok(entries) => HttpResponse::Ok().json(entries),
+            Err(e) => admin_error(e),
+        })
+}
+
+pub fn start_maintenance(
+    req: HttpRequest,
+    maintenance: web::Json<Maintenance>,
+    server: web::Data<Addr<game::Host>>,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    server
+        .send(game::StartMaintenance {
+            token: bearer(&req),
+            seconds: maintenance.seconds,
+        })
+        .map_err(error::ErrorInternalServerError)
+        .map(done)
+}
+
+pub fn end_maintenance(
+    req: HttpRequest,
+    server: web::Data<Addr<game::Host>>,
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    server
+        .send(game::EndMaintenance {
+            token: bearer(&req),
+        })
+        .map_err(error::ErrorInternalServerError)
+        .map(done)
+}
@@ -0,0 +1,31 @@
+use crate::storage;
+use bridge::api::{AuditAction, AuditEntry};
+use bridge::UserId;
+use log::error;
+use serde::{Deserialize, Serialize};
+use std::io;
+
+const FILE: &str = "audit.json";
+
+/// Every moderator action, oldest first.
+#[derive(Serialize, Deserialize, Default)]
+pub struct AuditLog {
+    entries: Vec<AuditEntry>,
+}
+
+impl AuditLog {
+    pub fn load() -> io::Result<Self> {
+        storage::load(FILE)
+    }
+
+    pub fn record(&mut self, by: UserId, time: u64, action: AuditAction) {
+        self.entries.push(AuditEntry { by, time, action });
+        if let Err(e) = storage::save(FILE, self) {
+            error!("Failed to save audit log: {}", e);
+        }
+    }
+
+    pub fn entries(&self) -> &[AuditEntry] {
+        &self.entries
+    }
+}
@@ -0,0 +1,439 @@
+use actix::io::SinkWrite;
+use actix::*;
+use actix_codec::{AsyncRead, AsyncWrite, Framed};
+use awc::error::WsProtocolError;
+use awc::ws::{Codec, Frame, Message as WsMessage};
+use awc::Client;
+use futures::stream::{SplitSink, Stream};
+
+use bridge::{GameKind, RoomBrief, RoomId, UserId};
+use log::{error, info};
+use rand::Rng;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+const PROBE_PREFIX: &str = "lt:";
+
+struct Config {
+    url: String,
+    users: usize,
+    spawn_interval: Duration,
+    chat_interval: Duration,
+    create_ratio: f64,
+    disconnect_chance: f64,
+    duration: Duration,
+}
+
+impl Config {
+    fn from_args() -> Self {
+        let mut config = Config {
+            url: "http://127.0.0.1:8080/ws/".into(),
+            users: 100,
+            spawn_interval: Duration::from_millis(10),
+            chat_interval: Duration::from_millis(1000),
+            create_ratio: 0.1,
+            disconnect_chance: 0.01,
+            duration: Duration::from_secs(60),
+        };
+        let mut args = std::env::args().skip(1);
+        while let Some(flag) = args.next() {
+            let value = args.next().unwrap_or_else(|| usage(&flag));
+            match flag.as_str() {
+                "--url" => config.url = value,
+                "--users" => config.users = parse(&flag, &value),
+                "--spawn-interval-ms" => config.spawn_interval = interval(&flag, &value),
+                "--chat-interval-ms" => config.chat_interval = interval(&flag, &value),
+                "--create-ratio" => config.create_ratio = probability(&flag, &value),
+                "--disconnect-chance" => config.disconnect_chance = probability(&flag, &value),
+                "--duration" => config.duration = Duration::from_secs(parse(&flag, &value)),
+                _ => usage(&flag),
+            }
+        }
+        config
+    }
+}
+
+fn parse<T: std::str::FromStr>(flag: &str, value: &str) -> T {
+    value.parse().unwrap_or_else(|_| usage(flag))
+}
+
+/// `gen_bool` panics outside [0, 1], so those are turned away here.
+fn probability(flag: &str, value: &str) -> f64 {
+    let p: f64 = parse(flag, value);
+    if p >= 0.0 && p <= 1.0 {
+        p
+    } else {
+        usage(flag)
+    }
+}
+
+/// A zero interval would fire without pause, so it is turned away too.
+fn interval(flag: &str, value: &str) -> Duration {
+    match parse(flag, value) {
+        0 => usage(flag),
+        ms => Duration::from_millis(ms),
+    }
+}
+
+fn usage(flag: &str) -> ! {
+    eprintln!("Invalid argument: {}", flag);
+    eprintln!(
+        "Usage: kiwitu-loadtest [--url URL] [--users N] [--spawn-interval-ms MS] \
+         [--chat-interval-ms MS] [--create-ratio P] [--disconnect-chance P] [--duration SECS]"
+    );
+    std::process::exit(2);
+}
+
+#[derive(Message)]
+enum Record {
+    Connected,
+    ConnectFailed,
+    ProtocolError,
+    DecodeError,
+    Received,
+    Latency(Duration),
+    Disconnected,
+}
+
+#[derive(Message)]
+struct Report;
+
+#[derive(Default)]
+struct Stats {
+    started: Option<Instant>,
+    connected: usize,
+    online: usize,
+    peak_online: usize,
+    disconnected: usize,
+    connect_failed: usize,
+    protocol_errors: usize,
+    decode_errors: usize,
+    received: usize,
+    latencies: Vec<Duration>,
+}
+
+impl Actor for Stats {
+    type Context = Context<Self>;
+
+    fn started(&mut self, _: &mut Self::Context) {
+        self.started = Some(Instant::now());
+    }
+}
+
+impl Handler<Record> for Stats {
+    type Result = ();
+
+    fn handle(&mut self, record: Record, _: &mut Context<Self>) {
+        match record {
+            Record::Connected => {
+                self.connected += 1;
+                self.online += 1;
+                self.peak_online = self.peak_online.max(self.online);
+            }
+            Record::ConnectFailed => self.connect_failed += 1,
+            Record::ProtocolError => self.protocol_errors += 1,
+            Record::DecodeError => self.decode_errors += 1,
+            Record::Received => self.received += 1,
+            Record::Latency(latency) => self.latencies.push(latency),
+            Record::Disconnected => {
+                self.disconnected += 1;
+                self.online = self.online.saturating_sub(1);
+            }
+        }
+    }
+}
+
+impl Handler<Report> for Stats {
+    type Result = ();
+
+    fn handle(&mut self, _: Report, _: &mut Context<Self>) {
+        let elapsed = self.started.map_or(0.0, |started| {
+            let elapsed = started.elapsed();
+            elapsed.as_secs() as f64 + f64::from(elapsed.subsec_millis()) / 1000.0
+        });
+        self.latencies.sort();
+        println!("== kiwitu load test ({:.1}s) ==", elapsed);
+        println!(
+            "sessions: {} connected, {} peak online, {} disconnected",
+            self.connected, self.peak_online, self.disconnected
+        );
+        println!(
+            "errors: {} connect, {} protocol, {} decode",
+            self.connect_failed, self.protocol_errors, self.decode_errors
+        );
+        println!(
+            "received: {} messages ({:.0}/s)",
+            self.received,
+            self.received as f64 / elapsed.max(1.0)
+        );
+        println!("chat round trips: {}", self.latencies.len());
+        for &(label, rank) in &[("p50", 0.5), ("p90", 0.9), ("p99", 0.99), ("max", 1.0)] {
+            match percentile(&self.latencies, rank) {
+                Some(latency) => println!("  {}: {:?}", label, latency),
+                None => println!("  {}: -", label),
+            }
+        }
+        System::current().stop();
+    }
+}
+
+fn percentile(sorted: &[Duration], rank: f64) -> Option<Duration> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let index = ((sorted.len() as f64 * rank).ceil() as usize).max(1) - 1;
+    Some(sorted[index.min(sorted.len() - 1)])
+}
+
+struct Spawner {
+    config: Config,
+    stats: Addr<Stats>,
+    online: usize,
+}
+
+#[derive(Message)]
+struct Gone;
+
+impl Actor for Spawner {
+    type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        info!(
+            "Spawning {} users against {}",
+            self.config.users, self.config.url
+        );
+        ctx.run_interval(self.config.spawn_interval, |actor, ctx| {
+            if actor.online < actor.config.users {
+                actor.online += 1;
+                actor.spawn_user(ctx);
+            }
+        });
+        ctx.run_later(self.config.duration, |actor, _| {
+            actor.stats.do_send(Report);
+        });
+    }
+}
+
+impl Handler<Gone> for Spawner {
+    type Result = ();
+
+    fn handle(&mut self, _: Gone, _: &mut Context<Self>) {
+        // The next spawn tick replaces the user to keep the population stable.
+        self.online -= 1;
+    }
+}
+
+impl Spawner {
+    fn spawn_user(&mut self, ctx: &mut Context<Self>) {
+        let stats = self.stats.clone();
+        let spawner = ctx.address();
+        let chat_interval = self.config.chat_interval;
+        let create_ratio = self.config.create_ratio;
+        let disconnect_chance = self.config.disconnect_chance;
+        Client::new()
+            .ws(self.config.url.as_str())
+            .connect()
+            .into_actor(self)
+            .then(move |result, _, _| {
+                match result {
+                    Ok((_, framed)) => {
+                        stats.do_send(Record::Connected);
+                        let (sink, stream) = framed.split();
+                        SimUser::create(move |ctx| {
+                            SimUser::add_stream(stream, ctx);
+                            SimUser {
+                                sink: SinkWrite::new(sink, ctx),
+                                id: None,
+                                rooms: vec![],
+                                in_room: false,
+                                probes: HashMap::new(),
+                                next_probe: 0,
+                                stats,
+                                spawner,
+                                chat_interval,
+                                create_ratio,
+                                disconnect_chance,
+                            }
+                        });
+                    }
+                    Err(e) => {
+                        error!("Connect failed: {}", e);
+                        stats.do_send(Record::ConnectFailed);
+                        spawner.do_send(Gone);
+                    }
+                }
+                fut::ok(())
+            })
+            .spawn(ctx);
+    }
+}
+
+struct SimUser<T>
+where
+    T: AsyncRead + AsyncWrite,
+{
+    sink: SinkWrite<SplitSink<Framed<T, Codec>>>,
+    id: Option<UserId>,
+    rooms: Vec<RoomId>,
+    in_room: bool,
+    probes: HashMap<u64, Instant>,
+    next_probe: u64,
+    stats: Addr<Stats>,
+    spawner: Addr<Spawner>,
+    chat_interval: Duration,
+    create_ratio: f64,
+    disconnect_chance: f64,
+}
+
+impl<T: 'static> Actor for SimUser<T>
+where
+    T: AsyncRead + AsyncWrite,
+{
+    type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        ctx.run_interval(self.chat_interval, |actor, ctx| {
+            if rand::thread_rng().gen_bool(actor.disconnect_chance) {
+                let _ = actor.sink.write(WsMessage::Close(None));
+                ctx.stop();
+                return;
+            }
+            if actor.in_room {
+                actor.probe();
+            }
+        });
+    }
+
+    fn stopped(&mut self, _: &mut Self::Context) {
+        self.stats.do_send(Record::Disconnected);
+        self.spawner.do_send(Gone);
+    }
+}
+
+impl<T: 'static> SimUser<T>
+where
+    T: AsyncRead + AsyncWrite,
+{
+    fn send(&mut self, message: bridge::client::Message) {
+        let binary = serde_json::to_vec(&message).unwrap();
+        if self.sink.write(WsMessage::Binary(binary.into())).is_err() {
+            self.stats.do_send(Record::ProtocolError);
+        }
+    }
+
+    fn probe(&mut self) {
+        let nonce = self.next_probe;
+        self.next_probe += 1;
+        self.probes.insert(nonce, Instant::now());
+        self.send(bridge::client::Message::Chat {
+            text: format!("{}{}", PROBE_PREFIX, nonce),
+            to: None,
+            team: false,
+        });
+    }
+
+    fn pick_room(&mut self) {
+        self.in_room = false;
+        let mut rng = rand::thread_rng();
+        if self.rooms.is_empty() || rng.gen_bool(self.create_ratio) {
+            self.send(bridge::client::Message::CreateRoom {
+                room: RoomBrief {
+                    id: 0,
+                    title: format!("loadtest {}", self.id.unwrap_or(0)),
+                    owner: 0,
+                    members: 0,
+                    capacity: 0,
+                    playing: false,
+                    separate_spectators: false,
+                    locked: false,
+                    game: GameKind::default(),
+                    rating: None,
+                },
+                password: None,
+            });
+        } else {
+            let room = self.rooms[rng.gen_range(0, self.rooms.len())];
+            self.send(bridge::client::Message::JoinRoom {
+                room,
+                password: None,
+            });
+        }
+    }
+
+    fn receive(&mut self, message: bridge::server::Message) {
+        use bridge::server::Message;
+        self.stats.do_send(Record::Received);
+        match message {
+            Message::Welcome { id, rooms, .. } => {
+                self.id = Some(id);
+                self.rooms = rooms.rooms.iter().map(|room| room.id).collect();
+                self.pick_room();
+            }
+            Message::NewRoom { room } => self.rooms.push(room.id),
+            Message::DestroyRoom { room } => self.rooms.retain(|&id| id != room),
+            Message::ReadyJoin { .. } => self.in_room = true,
+            Message::Chat { from, text, .. } => {
+                if Some(from) != self.id || !text.starts_with(PROBE_PREFIX) {
+                    return;
+                }
+                let sent = text[PROBE_PREFIX.len()..]
+                    .parse()
+                    .ok()
+                    .and_then(|nonce| self.probes.remove(&nonce));
+                if let Some(sent) = sent {
+                    self.stats.do_send(Record::Latency(sent.elapsed()));
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+impl<T: 'static> StreamHandler<Frame, WsProtocolError> for SimUser<T>
+where
+    T: AsyncRead + AsyncWrite,
+{
+    fn handle(&mut self, frame: Frame, ctx: &mut Context<Self>) {
+        match frame {
+            Frame::Binary(Some(binary)) => {
+                match serde_json::from_slice::<bridge::server::Message>(&binary) {
+                    Ok(message) => self.receive(message),
+                    Err(_) => self.stats.do_send(Record::DecodeError),
+                }
+            }
+            Frame::Ping(ping) => {
+                let _ = self.sink.write(WsMessage::Pong(ping));
+            }
+            Frame::Close(_) => ctx.stop(),
+            _ => (),
+        }
+    }
+
+    fn error(&mut self, _: WsProtocolError, _: &mut Context<Self>) -> Running {
+        self.stats.do_send(Record::ProtocolError);
+        Running::Stop
+    }
+
+    fn finished(&mut self, ctx: &mut Context<Self>) {
+        ctx.stop();
+    }
+}
+
+impl<T: 'static> actix::io::WriteHandler<WsProtocolError> for SimUser<T> where
+    T: AsyncRead + AsyncWrite
+{
+}
+
+fn main() -> std::io::Result<()> {
+    simple_logger::init_with_level(log::Level::Warn).unwrap();
+    let config = Config::from_args();
+    let sys = System::new("kiwitu-loadtest");
+    let stats = Stats::default().start();
+    Spawner {
+        config,
+        stats,
+        online: 0,
+    }
+    .start();
+    sys.run()
+}
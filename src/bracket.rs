@@ -0,0 +1,337 @@
+use bridge::{BracketMatch, BracketSide, Elimination, Slot, UserId};
+use serde::{Deserialize, Serialize};
+
+/// Where a match slot gets its player from.
+#[derive(Serialize, Deserialize, Clone, Copy)]
+enum Feed {
+    Entrant(Slot),
+    Winner(usize),
+    Loser(usize),
+}
+
+/// How the matches of a tournament lead into one another. The matches
+/// themselves live on the tournament, so clients see them as they are.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct Bracket {
+    feeds: Vec<[Feed; 2]>,
+    /// The second grand final, only played when the player from the
+    /// losers' side wins the first. Nobody is out before a second loss.
+    #[serde(default)]
+    reset: Option<usize>,
+}
+
+fn loser(game: &BracketMatch) -> Slot {
+    match game.winner {
+        Some(Slot::Player(winner)) if game.slots[0] == Slot::Player(winner) => game.slots[1],
+        Some(Slot::Player(_)) => game.slots[0],
+        Some(_) => Slot::Bye,
+        None => Slot::Waiting,
+    }
+}
+
+impl Bracket {
+    /// Seeds `entrants` in the order given, filling the first round up with
+    /// byes.
+    pub fn new(entrants: &[UserId], elimination: Elimination) -> (Self, Vec<BracketMatch>) {
+        let mut bracket = Self::default();
+        let mut matches = vec![];
+        let size = entrants.len().next_power_of_two().max(2);
+        let seeds = entrants
+            .iter()
+            .map(|&id| Slot::Player(id))
+            .chain(std::iter::repeat(Slot::Bye))
+            .take(size)
+            .collect::<Vec<_>>();
+        // Top seeds meet the byes.
+        let first = (0..size / 2)
+            .map(|i| {
+                let feeds = [Feed::Entrant(seeds[i]), Feed::Entrant(seeds[size - 1 - i])];
+                bracket.add(&mut matches, BracketSide::Winners, 1, feeds)
+            })
+            .collect::<Vec<_>>();
+        let mut winners = vec![first];
+        while winners[winners.len() - 1].len() > 1 {
+            let round = winners.len() as u32 + 1;
+            let feeds = winners[winners.len() - 1]
+                .iter()
+                .map(|&index| Feed::Winner(index))
+                .collect::<Vec<_>>();
+            let next = bracket.pair(&mut matches, BracketSide::Winners, round, &feeds);
+            winners.push(next);
+        }
+        if elimination == Elimination::Double {
+            let mut losers = winners[0]
+                .iter()
+                .map(|&index| Feed::Loser(index))
+                .collect::<Vec<_>>();
+            let mut round = 0;
+            if losers.len() > 1 {
+                round += 1;
+                losers = bracket.feed_pairs(&mut matches, round, &losers);
+            }
+            for dropping in winners[1..].iter() {
+                round += 1;
+                // Reversed so players who just met are kept apart.
+                losers = losers
+                    .iter()
+                    .zip(dropping.iter().rev())
+                    .map(|(&survivor, &index)| {
+                        let feeds = [survivor, Feed::Loser(index)];
+                        Feed::Winner(bracket.add(&mut matches, BracketSide::Losers, round, feeds))
+                    })
+                    .collect();
+                if losers.len() > 1 {
+                    round += 1;
+                    losers = bracket.feed_pairs(&mut matches, round, &losers);
+                }
+            }
+            let champion = Feed::Winner(winners[winners.len() - 1][0]);
+            let first = bracket.add(&mut matches, BracketSide::Final, 1, [champion, losers[0]]);
+            let feeds = [Feed::Winner(first), Feed::Loser(first)];
+            bracket.reset = Some(bracket.add(&mut matches, BracketSide::Final, 2, feeds));
+        }
+        (bracket, matches)
+    }
+
+    fn add(
+        &mut self,
+        matches: &mut Vec<BracketMatch>,
+        side: BracketSide,
+        round: u32,
+        feeds: [Feed; 2],
+    ) -> usize {
+        self.feeds.push(feeds);
+        matches.push(BracketMatch {
+            side,
+            round,
+            slots: [Slot::Waiting; 2],
+            winner: None,
+            room: None,
+        });
+        matches.len() - 1
+    }
+
+    fn pair(
+        &mut self,
+        matches: &mut Vec<BracketMatch>,
+        side: BracketSide,
+        round: u32,
+        feeds: &[Feed],
+    ) -> Vec<usize> {
+        feeds
+            .chunks(2)
+            .map(|pair| self.add(matches, side, round, [pair[0], pair[1]]))
+            .collect()
+    }
+
+    fn feed_pairs(
+        &mut self,
+        matches: &mut Vec<BracketMatch>,
+        round: u32,
+        feeds: &[Feed],
+    ) -> Vec<Feed> {
+        self.pair(matches, BracketSide::Losers, round, feeds)
+            .into_iter()
+            .map(Feed::Winner)
+            .collect()
+    }
+
+    /// Moves players along as far as they can go, deciding matches where
+    /// only one side is still in. Feeds only point back, so one pass in
+    /// order reaches everything.
+    pub fn settle(&self, matches: &mut [BracketMatch], forfeited: &[UserId]) {
+        for index in 0..matches.len() {
+            if matches[index].winner.is_some() {
+                continue;
+            }
+            for side in 0..2 {
+                if matches[index].slots[side] == Slot::Waiting {
+                    matches[index].slots[side] = match self.feeds[index][side] {
+                        Feed::Entrant(slot) => slot,
+                        Feed::Winner(from) => matches[from].winner.unwrap_or(Slot::Waiting),
+                        Feed::Loser(from) => loser(&matches[from]),
+                    };
+                }
+            }
+            // The winners' champion taking the first final ends it there.
+            if self.reset == Some(index) {
+                if let Feed::Winner(first) = self.feeds[index][0] {
+                    let game = &matches[first];
+                    if game.winner.is_some() && game.winner == Some(game.slots[0]) {
+                        matches[index].slots[1] = Slot::Bye;
+                    }
+                }
+            }
+            let slots = matches[index].slots;
+            if slots.contains(&Slot::Waiting) {
+                continue;
+            }
+            let mut present = slots.iter().filter(|slot| match slot {
+                Slot::Player(id) => !forfeited.contains(id),
+                _ => false,
+            });
+            matches[index].winner = match (present.next(), present.next()) {
+                (Some(_), Some(_)) => continue,
+                (Some(&slot), None) => Some(slot),
+                _ => Some(Slot::Bye),
+            };
+        }
+    }
+}
+
+/// Matches with both players in that still need playing.
+pub fn ready(matches: &[BracketMatch]) -> Vec<(usize, [UserId; 2])> {
+    matches
+        .iter()
+        .enumerate()
+        .filter(|(_, game)| game.winner.is_none())
+        .filter_map(|(index, game)| match game.slots {
+            [Slot::Player(a), Slot::Player(b)] => Some((index, [a, b])),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    /// Plays every match as it gets ready, letting `pick` choose the
+    /// winner, and returns the matches with how often each player lost.
+    fn play<F>(
+        elimination: Elimination,
+        size: usize,
+        forfeited: &[UserId],
+        mut pick: F,
+    ) -> (Vec<BracketMatch>, HashMap<UserId, usize>)
+    where
+        F: FnMut(usize, [UserId; 2]) -> UserId,
+    {
+        let entrants = (1..=size).collect::<Vec<_>>();
+        let (bracket, mut matches) = Bracket::new(&entrants, elimination);
+        let mut losses = HashMap::new();
+        bracket.settle(&mut matches, forfeited);
+        while let Some(&(index, players)) = ready(&matches).first() {
+            let winner = pick(index, players);
+            let loser = if winner == players[0] {
+                players[1]
+            } else {
+                players[0]
+            };
+            *losses.entry(loser).or_insert(0) += 1;
+            matches[index].winner = Some(Slot::Player(winner));
+            bracket.settle(&mut matches, forfeited);
+        }
+        (matches, losses)
+    }
+
+    fn champion(matches: &[BracketMatch]) -> Option<Slot> {
+        matches.last().and_then(|game| game.winner)
+    }
+
+    #[test]
+    fn single_elimination_sizes() {
+        for &size in [2, 3, 5, 8].iter() {
+            let (matches, losses) = play(Elimination::Single, size, &[], |_, [a, b]| a.max(b));
+            assert!(matches.iter().all(|game| game.winner.is_some()));
+            assert_eq!(champion(&matches), Some(Slot::Player(size)));
+            assert_eq!(losses.len(), size - 1, "size {}", size);
+            assert!(losses.values().all(|&count| count == 1));
+        }
+    }
+
+    #[test]
+    fn double_elimination_sizes() {
+        for &size in [2, 3, 5, 8].iter() {
+            let (matches, losses) = play(Elimination::Double, size, &[], |_, [a, b]| a.max(b));
+            assert!(matches.iter().all(|game| game.winner.is_some()));
+            assert_eq!(champion(&matches), Some(Slot::Player(size)));
+            assert_eq!(losses.len(), size - 1, "size {}", size);
+            assert!(losses.values().all(|&count| count == 2), "size {}", size);
+        }
+    }
+
+    #[test]
+    fn double_elimination_never_drops_a_player_after_one_loss() {
+        for &size in [2, 3, 5, 8].iter() {
+            // Upsets everywhere, so champions come from the losers' side too.
+            let (matches, losses) = play(Elimination::Double, size, &[], |index, [a, b]| {
+                if index % 2 == 0 {
+                    a.min(b)
+                } else {
+                    a.max(b)
+                }
+            });
+            assert!(matches.iter().all(|game| game.winner.is_some()));
+            let champion = match champion(&matches) {
+                Some(Slot::Player(id)) => id,
+                other => panic!("size {} ended with {:?}", size, other),
+            };
+            assert!(losses.get(&champion).map_or(true, |&count| count < 2));
+            for (&player, &count) in losses.iter() {
+                assert!(count <= 2, "size {}: {} lost {} times", size, player, count);
+                assert!(
+                    player == champion || count == 2,
+                    "size {}: {} out early",
+                    size,
+                    player
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn byes_go_to_top_seeds() {
+        let entrants = [1, 2, 3];
+        let (bracket, mut matches) = Bracket::new(&entrants, Elimination::Single);
+        bracket.settle(&mut matches, &[]);
+        assert_eq!(matches[0].slots, [Slot::Player(1), Slot::Bye]);
+        assert_eq!(matches[0].winner, Some(Slot::Player(1)));
+        assert_eq!(ready(&matches), vec![(1, [2, 3])]);
+    }
+
+    #[test]
+    fn forfeit_sends_the_opponent_through() {
+        let (matches, losses) = play(Elimination::Single, 4, &[4], |_, [a, b]| a.min(b));
+        // 1 would have met 4 first, and never had to play that match.
+        assert_eq!(matches[0].slots, [Slot::Player(1), Slot::Player(4)]);
+        assert_eq!(matches[0].winner, Some(Slot::Player(1)));
+        assert!(!losses.contains_key(&4));
+        assert_eq!(champion(&matches), Some(Slot::Player(1)));
+    }
+
+    #[test]
+    fn everyone_forfeiting_ends_without_a_champion() {
+        let (matches, losses) = play(Elimination::Double, 2, &[1, 2], |_, [a, _]| a);
+        assert!(losses.is_empty());
+        assert!(matches.iter().all(|game| game.winner == Some(Slot::Bye)));
+    }
+
+    #[test]
+    fn grand_final_resets_after_the_first_loss_of_the_winners_champion() {
+        // 2 wins the winners' side, then loses the first final to 1.
+        let (matches, _) = play(Elimination::Double, 2, &[], |index, [a, b]| {
+            if index == 0 {
+                a.max(b)
+            } else {
+                a.min(b)
+            }
+        });
+        let finals = matches
+            .iter()
+            .filter(|game| game.side == BracketSide::Final)
+            .collect::<Vec<_>>();
+        assert_eq!(finals.len(), 2);
+        assert_eq!(finals[1].slots, [Slot::Player(1), Slot::Player(2)]);
+        assert_eq!(champion(&matches), Some(Slot::Player(1)));
+    }
+
+    #[test]
+    fn grand_final_needs_no_reset_when_the_winners_champion_wins() {
+        let (matches, _) = play(Elimination::Double, 2, &[], |_, [a, b]| a.max(b));
+        let reset = &matches[matches.len() - 1];
+        assert_eq!(reset.slots, [Slot::Player(2), Slot::Bye]);
+        assert_eq!(champion(&matches), Some(Slot::Player(2)));
+    }
+}
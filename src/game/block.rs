@@ -0,0 +1,53 @@
+use super::Host;
+use actix::prelude::*;
+use bridge::server::Message;
+use bridge::*;
+
+/// Adds a user to, or with `block: false` removes them from, the block list.
+#[derive(Message)]
+pub struct Block {
+    pub id: UserId,
+    pub user: UserId,
+    pub block: bool,
+}
+
+impl Host {
+    pub(super) fn send_blocked(&self, id: UserId) {
+        if let Some(session) = self.sessions.get(&id) {
+            let _ = session.pipe.do_send(Message::BlockList {
+                users: session.blocked.iter().cloned().collect(),
+            });
+        }
+    }
+}
+
+impl Handler<Block> for Host {
+    type Result = ();
+
+    fn handle(&mut self, message: Block, _: &mut Context<Self>) {
+        let (id, user) = (message.id, message.user);
+        if id == user {
+            return;
+        }
+        let session = match self.sessions.get_mut(&id) {
+            Some(session) => session,
+            None => return,
+        };
+        let changed = if message.block {
+            session.blocked.insert(user)
+        } else {
+            session.blocked.remove(&user)
+        };
+        if !changed {
+            return;
+        }
+        let blocked = session.blocked.clone();
+        self.sync_room(id);
+        // Guests keep their list only for as long as they stay connected.
+        if let Some(account) = self.accounts.get_mut(id) {
+            account.blocked = blocked;
+            self.accounts.save();
+        }
+        self.send_blocked(id);
+    }
+}
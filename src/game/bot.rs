@@ -0,0 +1,189 @@
+use super::play::{self, Strategy};
+use super::room::{Play, Room};
+use super::{Control, Host, Session};
+use actix::prelude::*;
+use bridge::server::Message;
+use bridge::*;
+use log::info;
+use rand::Rng;
+use std::collections::BTreeSet;
+use std::time::Duration;
+
+/// Bots wait a little before they move, as a person would.
+const THINK_TIME: Duration = Duration::from_millis(1500);
+const THINK_JITTER_MS: u64 = 1500;
+
+/// Only the room owner may add bots.
+#[derive(Message)]
+pub struct AddBot {
+    pub id: UserId,
+}
+
+#[derive(Message)]
+pub struct RemoveBot {
+    pub id: UserId,
+    pub bot: UserId,
+}
+
+/// A session played by the server. It gets the messages a socket would and
+/// moves through the same room messages a socket does.
+struct Bot {
+    id: UserId,
+    room: Option<Addr<Room>>,
+    strategy: Box<dyn Strategy>,
+    /// The move it is thinking over.
+    pending: Option<SpawnHandle>,
+}
+
+impl Actor for Bot {
+    type Context = Context<Self>;
+}
+
+impl Handler<Message> for Bot {
+    type Result = ();
+
+    fn handle(&mut self, message: Message, ctx: &mut Context<Self>) {
+        let view = match message {
+            Message::GameUpdate { view } => view,
+            Message::GameResult { .. } => {
+                self.strategy.reset();
+                return;
+            }
+            _ => return,
+        };
+        if let Some(pending) = self.pending.take() {
+            ctx.cancel_future(pending);
+        }
+        let action = match self.strategy.act(self.id, &view) {
+            Some(action) => action,
+            None => return,
+        };
+        let delay =
+            THINK_TIME + Duration::from_millis(rand::thread_rng().gen_range(0, THINK_JITTER_MS));
+        self.pending = Some(ctx.run_later(delay, move |bot, _| {
+            bot.pending = None;
+            if let Some(room) = &bot.room {
+                room.do_send(Play { id: bot.id, action });
+            }
+        }));
+    }
+}
+
+impl Handler<Control> for Bot {
+    type Result = ();
+
+    fn handle(&mut self, message: Control, ctx: &mut Context<Self>) {
+        match message {
+            Control::Enter(room) => self.room = Some(room),
+            Control::Leave => self.room = None,
+            Control::Close(_) => ctx.stop(),
+        }
+    }
+}
+
+impl Host {
+    /// The room `id` is in, if they own it.
+    fn owned_room(&self, id: UserId) -> Option<RoomId> {
+        let room_id = self.sessions.get(&id)?.room?;
+        let room = self.rooms.get(&room_id)?;
+        if room.brief.owner == id && !self.sessions[&id].spectating {
+            Some(room_id)
+        } else {
+            None
+        }
+    }
+
+    /// Sends the bots of a room away once no person is left to play.
+    pub(super) fn dismiss_bots(&mut self, room_id: RoomId, ctx: &mut Context<Self>) {
+        let bots = match self.rooms.get(&room_id) {
+            Some(room) if room.members.iter().all(|id| room.bots.contains(id)) => room.bots.clone(),
+            _ => return,
+        };
+        for bot in bots {
+            self.close_session(bot, DisconnectReason::Kicked, ctx);
+        }
+    }
+}
+
+impl Handler<AddBot> for Host {
+    type Result = ();
+
+    fn handle(&mut self, message: AddBot, _: &mut Context<Self>) {
+        let room_id = match self.owned_room(message.id) {
+            Some(room_id) => room_id,
+            None => return,
+        };
+        let room = &self.rooms[&room_id];
+        // Bots have no rating to let them in with.
+        let refusal = if let Some(range) = room.brief.rating {
+            Some(Alert::RatingOutOfRange { range })
+        } else if room.members.len() >= room.brief.capacity {
+            Some(Alert::RoomFull)
+        } else if room.brief.playing {
+            Some(Alert::GameInProgress)
+        } else {
+            None
+        };
+        if let Some(alert) = refusal {
+            self.alert(message.id, alert);
+            return;
+        }
+        let strategy = play::new_strategy(room.brief.game, &self.dictionary);
+        let (id, name) = self.generate_guest();
+        let name = name.replacen("GUEST_", "BOT_", 1);
+        let bot = Bot {
+            id,
+            room: None,
+            strategy,
+            pending: None,
+        }
+        .start();
+        self.next_connection += 1;
+        self.sessions.insert(
+            id,
+            Session {
+                id,
+                name: name.clone(),
+                pipe: bot.clone().recipient(),
+                control: bot.recipient(),
+                room: None,
+                spectating: false,
+                blocked: BTreeSet::new(),
+                ip: None,
+                muted: None,
+                resume: String::new(),
+                connection: self.next_connection,
+                bot: true,
+                automated: false,
+            },
+        );
+        for session in self.sessions.values() {
+            let _ = session.pipe.do_send(Message::Connected {
+                user: UserBrief {
+                    id,
+                    name: name.clone(),
+                    bot: true,
+                },
+            });
+        }
+        info!("Bot {} added to room #{}", name, room_id);
+        if let Some(room) = self.rooms.get_mut(&room_id) {
+            room.bots.insert(id);
+        }
+        self.enter(id, room_id);
+    }
+}
+
+impl Handler<RemoveBot> for Host {
+    type Result = ();
+
+    fn handle(&mut self, message: RemoveBot, ctx: &mut Context<Self>) {
+        let room_id = match self.owned_room(message.id) {
+            Some(room_id) => room_id,
+            None => return,
+        };
+        if self.rooms[&room_id].bots.contains(&message.bot) {
+            self.close_session(message.bot, DisconnectReason::Kicked, ctx);
+        }
+    }
+}
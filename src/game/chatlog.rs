@@ -0,0 +1,83 @@
+use super::unix_time;
+use bridge::api::ChatRecord;
+use bridge::*;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// How many lines a lobby or room remembers for reports.
+const LOG_SIZE: usize = 200;
+/// Lines kept on each side of a reported line.
+const CONTEXT: usize = 10;
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Chat ids are unique across the lobby and every room actor.
+fn next_id() -> ChatId {
+    NEXT_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+#[derive(Default, Clone, Serialize, Deserialize)]
+pub(super) struct ChatLog {
+    records: VecDeque<ChatRecord>,
+}
+
+fn visible(record: &ChatRecord, viewer: UserId) -> bool {
+    match record.to {
+        Some(to) => to == viewer || record.from == viewer,
+        None => true,
+    }
+}
+
+impl ChatLog {
+    /// Keeps new ids clear of the ones in a restored log.
+    pub(super) fn reserve_ids(&self) {
+        if let Some(last) = self.records.iter().map(|record| record.id).max() {
+            if NEXT_ID.load(Ordering::Relaxed) <= last {
+                NEXT_ID.store(last + 1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    pub(super) fn push(
+        &mut self,
+        from: UserId,
+        to: Option<UserId>,
+        room: Option<RoomId>,
+        text: &str,
+        scope: ChatScope,
+    ) -> ChatId {
+        let id = next_id();
+        if self.records.len() >= LOG_SIZE {
+            self.records.pop_front();
+        }
+        self.records.push_back(ChatRecord {
+            id,
+            from,
+            to,
+            room,
+            text: text.to_string(),
+            scope,
+            time: unix_time(),
+        });
+        id
+    }
+
+    /// Lines around `id` as `viewer` saw them, or `None` when `viewer`
+    /// never saw that line.
+    pub(super) fn context(&self, id: ChatId, viewer: UserId) -> Option<Vec<ChatRecord>> {
+        let index = self.records.iter().position(|record| record.id == id)?;
+        if !visible(&self.records[index], viewer) {
+            return None;
+        }
+        Some(
+            self.records
+                .iter()
+                .skip(index.saturating_sub(CONTEXT))
+                .take(CONTEXT * 2 + 1)
+                .filter(|record| visible(record, viewer))
+                .cloned()
+                .collect(),
+        )
+    }
+}
@@ -0,0 +1,184 @@
+use super::Host;
+use actix::prelude::*;
+use bridge::server::Message;
+use bridge::*;
+
+#[derive(Message)]
+pub struct RequestFriend {
+    pub id: UserId,
+    pub user: UserId,
+}
+
+#[derive(Message)]
+pub struct AcceptFriend {
+    pub id: UserId,
+    pub user: UserId,
+}
+
+/// Removes a friend, or declines a pending request from them.
+#[derive(Message)]
+pub struct RemoveFriend {
+    pub id: UserId,
+    pub user: UserId,
+}
+
+impl Host {
+    fn send(&self, id: UserId, message: Message) {
+        if let Some(session) = self.sessions.get(&id) {
+            let _ = session.pipe.do_send(message);
+        }
+    }
+
+    fn presence(&self, id: UserId) -> Presence {
+        match self.sessions.get(&id) {
+            Some(session) => match session.room {
+                Some(room) => Presence::InRoom(room),
+                None => Presence::Lobby,
+            },
+            None => Presence::Offline,
+        }
+    }
+
+    fn brief(&self, id: UserId) -> Option<UserBrief> {
+        self.accounts.get(id).map(|account| UserBrief {
+            id,
+            name: account.name.clone(),
+            bot: account.bot,
+        })
+    }
+
+    fn friend(&self, id: UserId) -> Option<Friend> {
+        self.brief(id).map(|user| Friend {
+            user,
+            presence: self.presence(id),
+        })
+    }
+
+    /// Sends the full friend list to an account that just connected.
+    pub(super) fn send_friends(&self, id: UserId) {
+        if let Some(account) = self.accounts.get(id) {
+            self.send(
+                id,
+                Message::Friends {
+                    friends: account
+                        .friends
+                        .iter()
+                        .filter_map(|&friend| self.friend(friend))
+                        .collect(),
+                    requests: account
+                        .requests
+                        .iter()
+                        .filter_map(|&from| self.brief(from))
+                        .collect(),
+                },
+            );
+        }
+    }
+
+    /// Tells online friends where the user is now.
+    pub(super) fn notify_presence(&self, id: UserId) {
+        let friend = match self.friend(id) {
+            Some(friend) => friend,
+            None => return,
+        };
+        if let Some(account) = self.accounts.get(id) {
+            for &other in account.friends.iter() {
+                self.send(
+                    other,
+                    Message::FriendChanged {
+                        friend: friend.clone(),
+                    },
+                );
+            }
+        }
+    }
+
+    fn befriend(&mut self, a: UserId, b: UserId) {
+        for &(me, other) in &[(a, b), (b, a)] {
+            if let Some(account) = self.accounts.get_mut(me) {
+                account.requests.remove(&other);
+                account.friends.insert(other);
+            }
+        }
+        self.accounts.save();
+        for &(me, other) in &[(a, b), (b, a)] {
+            if let Some(friend) = self.friend(other) {
+                self.send(me, Message::FriendChanged { friend });
+            }
+        }
+    }
+}
+
+impl Handler<RequestFriend> for Host {
+    type Result = ();
+
+    fn handle(&mut self, message: RequestFriend, _: &mut Context<Self>) {
+        let (id, user) = (message.id, message.user);
+        if id == user || self.accounts.get(id).is_none() {
+            self.alert(id, Alert::AccountRequired);
+            return;
+        }
+        let (already, crossed) = match self.accounts.get(id) {
+            Some(account) => (
+                account.friends.contains(&user),
+                account.requests.contains(&user),
+            ),
+            None => return,
+        };
+        if already {
+            return;
+        }
+        // Both asked each other, so there is nothing left to wait for.
+        if crossed {
+            self.befriend(id, user);
+            return;
+        }
+        match self.accounts.get_mut(user) {
+            Some(target) => {
+                target.requests.insert(id);
+            }
+            None => {
+                self.alert(id, Alert::AccountRequired);
+                return;
+            }
+        }
+        self.accounts.save();
+        if let Some(from) = self.brief(id) {
+            self.send(user, Message::FriendRequested { from });
+        }
+        self.alert(id, Alert::FriendRequestSent { user });
+    }
+}
+
+impl Handler<AcceptFriend> for Host {
+    type Result = ();
+
+    fn handle(&mut self, message: AcceptFriend, _: &mut Context<Self>) {
+        let requested = self
+            .accounts
+            .get(message.id)
+            .map_or(false, |account| account.requests.contains(&message.user));
+        if requested {
+            self.befriend(message.id, message.user);
+        }
+    }
+}
+
+impl Handler<RemoveFriend> for Host {
+    type Result = ();
+
+    fn handle(&mut self, message: RemoveFriend, _: &mut Context<Self>) {
+        let (id, user) = (message.id, message.user);
+        for &(me, other) in &[(id, user), (user, id)] {
+            if let Some(account) = self.accounts.get_mut(me) {
+                account.friends.remove(&other);
+            }
+        }
+        if let Some(account) = self.accounts.get_mut(id) {
+            account.requests.remove(&user);
+        }
+        self.accounts.save();
+        self.send(id, Message::FriendRemoved { id: user });
+        self.send(user, Message::FriendRemoved { id });
+    }
+}
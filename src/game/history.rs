@@ -0,0 +1,62 @@
+use super::Host;
+use crate::replay;
+use actix::prelude::*;
+use bridge::server::Message;
+use bridge::*;
+use log::info;
+
+/// Most matches one request may ask for.
+pub const MAX_RECENT: usize = 50;
+
+/// Files a finished round and its replay, answering with the record as
+/// stored.
+#[derive(Message)]
+#[rtype(result = "MatchRecord")]
+pub(super) struct Finished {
+    pub(super) record: MatchRecord,
+    pub(super) frames: Vec<ReplayFrame>,
+    /// The tournament match the room was opened for, if any.
+    pub(super) bracket: Option<(TournamentId, usize)>,
+}
+
+#[derive(Message)]
+#[rtype(result = "Vec<MatchRecord>")]
+pub struct RecentMatches {
+    pub user: UserId,
+    pub limit: usize,
+}
+
+impl Handler<Finished> for Host {
+    type Result = MessageResult<Finished>;
+
+    fn handle(&mut self, message: Finished, ctx: &mut Context<Self>) -> Self::Result {
+        let record = self.matches.record(message.record);
+        self.rate(&record);
+        self.tournament_result(&record, message.bracket, ctx);
+        info!("Room #{} finished match #{}", record.room, record.id);
+        let mut frames = message.frames;
+        frames.push(ReplayFrame {
+            at: frames.last().map_or(0, |frame| frame.at),
+            message: Message::GameResult {
+                result: record.clone(),
+            },
+        });
+        replay::save(
+            &self.writer,
+            Replay {
+                record: record.clone(),
+                frames,
+            },
+        );
+        MessageResult(record)
+    }
+}
+
+impl Handler<RecentMatches> for Host {
+    type Result = MessageResult<RecentMatches>;
+
+    fn handle(&mut self, message: RecentMatches, _: &mut Context<Self>) -> Self::Result {
+        let limit = message.limit.min(MAX_RECENT);
+        MessageResult(self.matches.recent(message.user, limit))
+    }
+}
@@ -0,0 +1,179 @@
+use super::{unix_time, Host};
+use actix::actors::signal;
+use actix::prelude::*;
+use bridge::api::{AdminError, AuditAction};
+use bridge::server::Message;
+use bridge::*;
+use log::info;
+use std::time::{Duration, Instant};
+
+/// How long players get between a SIGTERM and the server going away.
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(30);
+const COUNTDOWN_STEP: Duration = Duration::from_secs(10);
+/// Time for the close frames to leave before the system stops.
+const EXIT_DELAY: Duration = Duration::from_secs(1);
+/// Longer countdowns are cut to this; an `Instant` overflows well before
+/// `u64::MAX` seconds.
+const MAX_MAINTENANCE_SECS: u64 = 7 * 24 * 60 * 60;
+
+pub(super) struct Maintenance {
+    end: Instant,
+    exit: bool,
+    countdown: SpawnHandle,
+    finish: SpawnHandle,
+}
+
+#[derive(Message)]
+#[rtype(result = "Result<(), AdminError>")]
+pub struct StartMaintenance {
+    pub token: String,
+    pub seconds: u64,
+}
+
+#[derive(Message)]
+#[rtype(result = "Result<(), AdminError>")]
+pub struct EndMaintenance {
+    pub token: String,
+}
+
+impl Host {
+    /// Seconds until maintenance starts, zero once it has.
+    pub(super) fn maintenance_left(&self) -> Option<u64> {
+        self.maintenance.as_ref().map(|maintenance| {
+            maintenance
+                .end
+                .saturating_duration_since(Instant::now())
+                .as_secs()
+        })
+    }
+
+    fn shutting_down(&self) -> bool {
+        self.maintenance
+            .as_ref()
+            .map_or(false, |maintenance| maintenance.exit)
+    }
+
+    /// Tells the user off when maintenance is coming.
+    pub(super) fn in_maintenance(&self, id: UserId) -> bool {
+        match self.maintenance_left() {
+            Some(seconds) => {
+                self.alert(id, Alert::Maintenance { seconds });
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn announce_maintenance(&self) {
+        if let Some(seconds) = self.maintenance_left() {
+            for session in self.sessions.values() {
+                let _ = session
+                    .pipe
+                    .do_send(Message::Alert(Alert::Maintenance { seconds }));
+            }
+        }
+    }
+
+    fn start_maintenance(&mut self, after: Duration, exit: bool, ctx: &mut Context<Self>) {
+        if let Some(maintenance) = self.maintenance.take() {
+            ctx.cancel_future(maintenance.countdown);
+            ctx.cancel_future(maintenance.finish);
+        }
+        info!("Maintenance in {} seconds", after.as_secs());
+        self.maintenance = Some(Maintenance {
+            end: Instant::now() + after,
+            exit,
+            countdown: ctx.run_interval(COUNTDOWN_STEP, |host, _| host.announce_maintenance()),
+            finish: ctx.run_later(after, |host, ctx| host.finish_maintenance(ctx)),
+        });
+        self.announce_maintenance();
+    }
+
+    /// Saves everything and sends every session away.
+    fn finish_maintenance(&mut self, ctx: &mut Context<Self>) {
+        let exit = match &self.maintenance {
+            Some(maintenance) => {
+                ctx.cancel_future(maintenance.countdown);
+                maintenance.exit
+            }
+            None => return,
+        };
+        self.announce_maintenance();
+        self.persist();
+        // Rooms close as their players go, so they are saved first.
+        self.save_snapshot(ctx, move |host, ctx| {
+            let ids = host.sessions.keys().cloned().collect::<Vec<_>>();
+            for id in ids {
+                host.close_session(id, DisconnectReason::Shutdown, ctx);
+            }
+            if exit {
+                info!("Shutting down");
+                ctx.run_later(EXIT_DELAY, |host, _| {
+                    host.flush();
+                    System::current().stop();
+                });
+            }
+        });
+    }
+}
+
+impl Handler<signal::Signal> for Host {
+    type Result = ();
+
+    fn handle(&mut self, message: signal::Signal, ctx: &mut Context<Self>) {
+        match message.0 {
+            signal::SignalType::Term | signal::SignalType::Int | signal::SignalType::Quit => {
+                // A second signal means the operator does not want to wait.
+                if self.shutting_down() {
+                    self.persist();
+                    self.save_snapshot(ctx, |host, _| {
+                        host.flush();
+                        System::current().stop();
+                    });
+                } else {
+                    self.start_maintenance(SHUTDOWN_GRACE, true, ctx);
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+impl Handler<StartMaintenance> for Host {
+    type Result = Result<(), AdminError>;
+
+    fn handle(&mut self, message: StartMaintenance, ctx: &mut Context<Self>) -> Self::Result {
+        let by = self.admin(&message.token)?;
+        if self.shutting_down() {
+            return Ok(());
+        }
+        let seconds = message.seconds.min(MAX_MAINTENANCE_SECS);
+        self.audit
+            .record(by, unix_time(), AuditAction::Maintenance { seconds });
+        self.start_maintenance(Duration::from_secs(seconds), false, ctx);
+        Ok(())
+    }
+}
+
+impl Handler<EndMaintenance> for Host {
+    type Result = Result<(), AdminError>;
+
+    fn handle(&mut self, message: EndMaintenance, ctx: &mut Context<Self>) -> Self::Result {
+        let by = self.admin(&message.token)?;
+        let maintenance = match self.maintenance.take() {
+            Some(maintenance) => maintenance,
+            None => return Ok(()),
+        };
+        // There is no taking back a shutdown.
+        if maintenance.exit {
+            self.maintenance = Some(maintenance);
+            return Ok(());
+        }
+        ctx.cancel_future(maintenance.countdown);
+        ctx.cancel_future(maintenance.finish);
+        info!("Maintenance ended");
+        self.audit
+            .record(by, unix_time(), AuditAction::MaintenanceEnded);
+        Ok(())
+    }
+}
@@ -0,0 +1,162 @@
+use bridge::*;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Players left alone this long get a fresh room that others can fill.
+pub(super) const SOLO_AFTER: Duration = Duration::from_secs(10);
+pub(super) const MATCH_INTERVAL: Duration = Duration::from_secs(1);
+const SKILL_TOLERANCE: i32 = 100;
+const SKILL_WIDENING: i32 = 50;
+const WAIT_SAMPLES: usize = 20;
+
+pub(super) struct Ticket {
+    pub(super) id: UserId,
+    pub(super) criteria: MatchCriteria,
+    since: Instant,
+}
+
+#[derive(Default)]
+pub(super) struct Matchmaker {
+    queue: Vec<Ticket>,
+    waits: VecDeque<Duration>,
+}
+
+impl Ticket {
+    fn waited(&self) -> Duration {
+        self.since.elapsed()
+    }
+
+    /// The skill gap this ticket accepts, widening the longer it waits.
+    /// Skills come from the client, so gaps are worked out in `i64`.
+    fn tolerance(&self) -> i64 {
+        let steps = (self.waited().as_secs() / 5).min(i32::max_value() as u64) as i64;
+        i64::from(SKILL_TOLERANCE) + i64::from(SKILL_WIDENING) * steps
+    }
+
+    pub(super) fn fits(&self, criteria: &MatchCriteria) -> bool {
+        let region = match (&self.criteria.region, &criteria.region) {
+            (Some(mine), Some(theirs)) => mine == theirs,
+            _ => true,
+        };
+        let skill = match (self.criteria.skill, criteria.skill) {
+            (Some(mine), Some(theirs)) => {
+                (i64::from(mine) - i64::from(theirs)).abs() <= self.tolerance()
+            }
+            _ => true,
+        };
+        let capacity = match (self.criteria.capacity, criteria.capacity) {
+            (Some(mine), Some(theirs)) => mine == theirs,
+            _ => true,
+        };
+        region && skill && capacity
+    }
+}
+
+impl Matchmaker {
+    pub(super) fn enqueue(&mut self, id: UserId, criteria: MatchCriteria) {
+        self.cancel(id);
+        self.queue.push(Ticket {
+            id,
+            criteria,
+            since: Instant::now(),
+        });
+    }
+
+    pub(super) fn cancel(&mut self, id: UserId) -> bool {
+        let before = self.queue.len();
+        self.queue.retain(|ticket| ticket.id != id);
+        self.queue.len() != before
+    }
+
+    /// Average of the most recent waits, or the solo timeout before any.
+    pub(super) fn estimate(&self) -> Duration {
+        if self.waits.is_empty() {
+            return SOLO_AFTER;
+        }
+        self.waits.iter().sum::<Duration>() / self.waits.len() as u32
+    }
+
+    fn record(&mut self, ticket: &Ticket) {
+        if self.waits.len() >= WAIT_SAMPLES {
+            self.waits.pop_front();
+        }
+        self.waits.push_back(ticket.waited());
+    }
+
+    /// Picks the fullest open room the criteria accept.
+    pub(super) fn best_room<'a>(
+        ticket: &Ticket,
+        rooms: impl Iterator<Item = (&'a RoomBrief, &'a MatchCriteria)>,
+    ) -> Option<RoomId> {
+        rooms
+            .filter(|(brief, _)| {
+                !brief.playing
+                    && !brief.locked
+                    && brief.rating.is_none()
+                    && brief.members < brief.capacity
+            })
+            .filter(|(brief, criteria)| {
+                ticket.fits(criteria)
+                    && ticket
+                        .criteria
+                        .capacity
+                        .map_or(true, |capacity| capacity == brief.capacity)
+            })
+            .max_by_key(|(brief, _)| (brief.members, std::cmp::Reverse(brief.id)))
+            .map(|(brief, _)| brief.id)
+    }
+
+    /// Empties the queue so the host can retry each ticket in turn.
+    pub(super) fn take_all(&mut self) -> Vec<Ticket> {
+        std::mem::replace(&mut self.queue, vec![])
+    }
+
+    pub(super) fn requeue(&mut self, ticket: Ticket) {
+        self.queue.push(ticket);
+    }
+
+    /// Removes a group of mutually compatible tickets to open a room with.
+    /// A lone ticket makes a group once it has waited long enough. Groups
+    /// never outgrow the room they are headed for.
+    pub(super) fn take_group(
+        &mut self,
+        min_capacity: usize,
+        max_capacity: usize,
+    ) -> Option<Vec<Ticket>> {
+        for i in 0..self.queue.len() {
+            let leader = &self.queue[i];
+            let limit = leader
+                .criteria
+                .capacity
+                .unwrap_or(max_capacity)
+                .max(min_capacity)
+                .min(max_capacity);
+            let mut group = vec![i];
+            for j in i + 1..self.queue.len() {
+                if group.len() >= limit {
+                    break;
+                }
+                let other = &self.queue[j];
+                if leader.fits(&other.criteria) && other.fits(&leader.criteria) {
+                    group.push(j);
+                }
+            }
+            if group.len() > 1 || leader.waited() >= SOLO_AFTER {
+                let mut tickets = vec![];
+                for &index in group.iter().rev() {
+                    tickets.push(self.queue.remove(index));
+                }
+                tickets.reverse();
+                for ticket in tickets.iter() {
+                    self.record(ticket);
+                }
+                return Some(tickets);
+            }
+        }
+        None
+    }
+
+    pub(super) fn placed(&mut self, ticket: &Ticket) {
+        self.record(ticket);
+    }
+}
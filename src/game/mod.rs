@@ -0,0 +1,1313 @@
+use crate::account::{Accounts, Check, Secret};
+use crate::audit::AuditLog;
+use crate::matches::Matches;
+use crate::ratelimit::{self, Throttle};
+use crate::rating::Ratings;
+use crate::report::Reports;
+use crate::sanction::Sanctions;
+use crate::storage::Writer;
+use actix::actors::signal;
+use actix::prelude::*;
+use bridge::api::{AuthError, Login, Sanction};
+use bridge::server::Message;
+use bridge::*;
+use log::info;
+use rand::distributions::Alphanumeric;
+use rand::{rngs::ThreadRng, Rng};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+mod block;
+mod bot;
+mod chatlog;
+mod friends;
+mod history;
+mod maintenance;
+mod matchmaking;
+mod moderation;
+mod play;
+mod ranking;
+mod room;
+mod snapshot;
+#[cfg(test)]
+mod tests;
+mod timer;
+mod tournament;
+pub use block::Block;
+pub use bot::{AddBot, RemoveBot};
+pub use friends::{AcceptFriend, RemoveFriend, RequestFriend};
+pub use history::RecentMatches;
+pub use maintenance::{EndMaintenance, StartMaintenance};
+use maintenance::Maintenance;
+pub use moderation::{
+    AddSanction, Admit, Kick, LiftSanction, ListAudit, ListReports, ListSanctions, Report,
+    ResolveReport,
+};
+use chatlog::ChatLog;
+use matchmaking::{Matchmaker, Ticket};
+pub use play::Dictionary;
+pub use ranking::Leaderboard;
+pub use room::{ChooseTeam, ClearTeams, Play, Room, ShuffleTeams, StartGame};
+pub use snapshot::Snapshot;
+use snapshot::{RestoredRoom, Resume};
+use tournament::TournamentEntry;
+pub use tournament::{
+    CancelTournament, CreateTournament, JoinTournament, LeaveTournament, StartTournament,
+};
+
+const ROOM_ARBITERS: usize = 4;
+const INVITE_TIMEOUT: Duration = Duration::from_secs(30);
+const RESUME_LEN: usize = 32;
+
+/// Seconds since the Unix epoch, as stored in the data files.
+pub fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+/// Milliseconds since the Unix epoch, for deadlines sent to clients.
+pub fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[derive(Message)]
+#[rtype(Welcome)]
+pub struct Connect {
+    pub addr: Recipient<Message>,
+    pub control: Recipient<Control>,
+    pub token: Option<String>,
+    /// Picks up a session saved in a snapshot.
+    pub resume: Option<String>,
+    pub ip: Option<String>,
+}
+
+#[derive(MessageResponse)]
+pub struct Welcome {
+    pub id: UserId,
+    pub connection: u64,
+    /// Signed in as a bot account, which has its own rate limit.
+    pub bot: bool,
+}
+
+/// Checks a sign-up before its password is hashed.
+#[derive(Message)]
+#[rtype(result = "Result<(), AuthError>")]
+pub struct CheckRegister {
+    pub name: String,
+    pub ip: Option<String>,
+}
+
+/// Makes an account with a password hashed off the host.
+#[derive(Message)]
+#[rtype(result = "Result<Login, AuthError>")]
+pub struct Register {
+    pub name: String,
+    pub secret: Secret,
+}
+
+/// Hands out what a login's password has to match, so it can be checked
+/// off the host.
+#[derive(Message)]
+#[rtype(result = "Result<Check, AuthError>")]
+pub struct LogIn {
+    pub name: String,
+    pub ip: Option<String>,
+}
+
+/// Signs in once the password passed its check.
+#[derive(Message)]
+#[rtype(result = "Result<Login, AuthError>")]
+pub struct Verified {
+    pub check: Check,
+    pub upgrade: Option<Secret>,
+}
+
+/// Makes a bot account owned by the holder of `token`.
+#[derive(Message)]
+#[rtype(result = "Result<Login, AuthError>")]
+pub struct CreateBot {
+    pub token: String,
+    pub name: String,
+}
+
+/// Replaces the token of a bot owned by the holder of `token`.
+#[derive(Message)]
+#[rtype(result = "Result<Login, AuthError>")]
+pub struct RotateBotToken {
+    pub token: String,
+    pub bot: UserId,
+}
+
+#[derive(Message)]
+#[rtype(result = "Result<(), AuthError>")]
+pub struct RevokeBotToken {
+    pub token: String,
+    pub bot: UserId,
+}
+
+/// Sent by a connection as it goes away. A user who logged in again
+/// elsewhere has a newer connection, which this leaves alone.
+#[derive(Message)]
+pub struct Disconnect {
+    pub id: UserId,
+    pub connection: u64,
+}
+
+#[derive(Message)]
+pub struct Chat {
+    pub id: UserId,
+    pub text: String,
+    pub to: Option<UserId>,
+    pub team: bool,
+}
+
+#[derive(Message)]
+pub struct CreateRoom {
+    pub id: UserId,
+    pub room: RoomBrief,
+    pub password: Option<String>,
+}
+
+#[derive(Message)]
+pub struct GetRoomDetail {
+    pub id: UserId,
+    pub room: RoomId,
+}
+
+#[derive(Message)]
+pub struct QueryRooms {
+    pub id: UserId,
+    pub query: RoomQuery,
+}
+
+#[derive(Message)]
+pub struct JoinRoom {
+    pub id: UserId,
+    pub room: RoomId,
+    pub password: Option<String>,
+}
+
+#[derive(Message)]
+pub struct Spectate {
+    pub id: UserId,
+    pub room: RoomId,
+}
+
+#[derive(Message)]
+pub struct QuitRoom {
+    pub id: UserId,
+}
+
+#[derive(Message)]
+pub struct InviteToRoom {
+    pub id: UserId,
+    pub user: UserId,
+}
+
+#[derive(Message)]
+pub struct AnswerInvite {
+    pub id: UserId,
+    pub room: RoomId,
+    pub accept: bool,
+}
+
+#[derive(Message)]
+pub struct QuickJoin {
+    pub id: UserId,
+    pub criteria: MatchCriteria,
+}
+
+#[derive(Message)]
+pub struct CancelQuickJoin {
+    pub id: UserId,
+}
+
+/// Routes a session's room traffic straight to its room actor.
+#[derive(Message)]
+pub enum Control {
+    Enter(Addr<Room>),
+    Leave,
+    /// Drops the connection, telling the client why.
+    Close(DisconnectReason),
+}
+
+#[derive(Clone)]
+struct Session {
+    id: UserId,
+    pipe: Recipient<Message>,
+    control: Recipient<Control>,
+    room: Option<RoomId>,
+    spectating: bool,
+    name: String,
+    /// Users whose chat this session never receives.
+    blocked: BTreeSet<UserId>,
+    ip: Option<String>,
+    /// Kept on the session so room actors can enforce it too.
+    muted: Option<Sanction>,
+    resume: String,
+    /// Tells this connection apart from older ones of the same user.
+    connection: u64,
+    /// Played by the server rather than a person.
+    bot: bool,
+    /// Signed in with a bot account.
+    automated: bool,
+}
+
+impl Session {
+    fn brief(&self) -> UserBrief {
+        UserBrief {
+            id: self.id,
+            name: self.name.clone(),
+            bot: self.bot || self.automated,
+        }
+    }
+}
+
+pub struct Host {
+    sessions: HashMap<UserId, Session>,
+    rng: ThreadRng,
+    rooms: HashMap<RoomId, RoomEntry>,
+    arbiters: Vec<Arbiter>,
+    next_arbiter: usize,
+    matchmaker: Matchmaker,
+    invites: HashMap<(UserId, RoomId), Invite>,
+    accounts: Accounts,
+    /// Lobby chat and whispers; rooms keep their own.
+    chat_log: ChatLog,
+    reports: Reports,
+    sanctions: Sanctions,
+    audit: AuditLog,
+    matches: Matches,
+    ratings: Ratings,
+    maintenance: Option<Maintenance>,
+    /// Sessions and rooms from a snapshot, waiting for their users.
+    resumes: HashMap<String, Resume>,
+    restored: HashMap<RoomId, RestoredRoom>,
+    next_connection: u64,
+    dictionary: Arc<Dictionary>,
+    /// Writes replays and snapshots off the host's thread.
+    writer: Writer,
+    /// Logins and sign-ups by address and by name.
+    attempts: Throttle,
+    tournaments: BTreeMap<TournamentId, TournamentEntry>,
+    next_tournament: TournamentId,
+}
+
+struct Invite {
+    from: UserId,
+    expiry: SpawnHandle,
+}
+
+/// The lobby's view of a room running in its own actor.
+struct RoomEntry {
+    addr: Addr<Room>,
+    brief: RoomBrief,
+    members: Vec<UserId>,
+    spectators: Vec<UserId>,
+    /// Members played by the server, who never own the room.
+    bots: BTreeSet<UserId>,
+    criteria: MatchCriteria,
+    password: Option<String>,
+    /// The owner saved in a snapshot, who gets the room back on returning
+    /// after someone else reopened it.
+    restored_owner: Option<UserId>,
+    /// Players of a round cut off by a restart, let back in while it goes
+    /// on.
+    awaited: Vec<UserId>,
+    /// The tournament and bracket match the room was opened for.
+    bracket: Option<(TournamentId, usize)>,
+}
+
+impl RoomEntry {
+    /// Keeps the brief in line with the member list, mirroring the
+    /// owner handover the room actor does on its own.
+    fn refresh(&mut self) {
+        if !self.members.contains(&self.brief.owner) {
+            let bots = &self.bots;
+            let owner = self
+                .members
+                .iter()
+                .find(|member| !bots.contains(member))
+                .or_else(|| self.members.first());
+            if let Some(&owner) = owner {
+                self.brief.owner = owner;
+            }
+        }
+        self.brief.members = self.members.len();
+    }
+}
+
+impl Host {
+    fn generate_guest(&mut self) -> (UserId, String) {
+        const NAME_LEN: u32 = 4;
+        let name_char = "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz"
+            .chars()
+            .collect::<Vec<char>>();
+        let id = loop {
+            let id = self.rng.gen_range(0, name_char.len().pow(NAME_LEN) - 1);
+            // Restored guests keep their ids until they give up on them.
+            if !self.sessions.contains_key(&id) && self.resumes.values().all(|resume| resume.id != id) {
+                break id;
+            }
+        };
+        let mut name = String::from("GUEST_");
+        let mut id_copy = id;
+        for _ in 0..NAME_LEN {
+            name.push(name_char[id_copy % name_char.len()]);
+            id_copy /= name_char.len();
+        }
+        (id, name)
+    }
+
+    fn query_rooms(&self, query: RoomQuery) -> RoomPage {
+        let mut rooms = self
+            .rooms
+            .values()
+            .map(|room| &room.brief)
+            .filter(|brief| query.matches(brief))
+            .collect::<Vec<_>>();
+        rooms.sort_by(|a, b| query.compare(a, b));
+        RoomPage {
+            total: rooms.len(),
+            rooms: rooms
+                .into_iter()
+                .skip(query.page.saturating_mul(ROOM_PAGE_SIZE))
+                .take(ROOM_PAGE_SIZE)
+                .cloned()
+                .collect(),
+            query,
+        }
+    }
+
+    fn send_match(&self, id: UserId, status: MatchStatus) {
+        if let Some(session) = self.sessions.get(&id) {
+            let _ = session.pipe.do_send(Message::Matchmaking { status });
+        }
+    }
+
+    /// Drops a queued ticket because the player went elsewhere on their own.
+    fn cancel_match(&mut self, id: UserId) {
+        if self.matchmaker.cancel(id) {
+            self.send_match(id, MatchStatus::Cancelled);
+        }
+    }
+
+    fn place(&mut self, ticket: &Ticket) -> bool {
+        let room = Matchmaker::best_room(
+            ticket,
+            self.rooms
+                .values()
+                .map(|room| (&room.brief, &room.criteria)),
+        );
+        match room {
+            Some(room) if self.enter(ticket.id, room) => {
+                self.send_match(ticket.id, MatchStatus::Matched { room });
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn run_matchmaking(&mut self, ctx: &mut Context<Self>) {
+        for ticket in self.matchmaker.take_all() {
+            if self.place(&ticket) {
+                self.matchmaker.placed(&ticket);
+            } else {
+                self.matchmaker.requeue(ticket);
+            }
+        }
+        // Groups wait for a new room, which maintenance would refuse.
+        if self.maintenance.is_some() {
+            return;
+        }
+        // Tickets left over from a group that fell through go back in line
+        // once the queue has been worked through.
+        let mut retry = vec![];
+        while let Some(mut group) = self.matchmaker.take_group(MIN_CAPACITY, MAX_CAPACITY) {
+            let rest = group.split_off(1);
+            let leader = &group[0];
+            let title = match self.sessions.get(&leader.id) {
+                Some(session) => format!("{}님의 빠른 대전", session.name),
+                None => {
+                    retry.extend(rest);
+                    continue;
+                }
+            };
+            self.handle(
+                CreateRoom {
+                    id: leader.id,
+                    room: RoomBrief {
+                        id: 0,
+                        title,
+                        owner: leader.id,
+                        members: 0,
+                        capacity: leader.criteria.capacity.unwrap_or(0),
+                        playing: false,
+                        separate_spectators: false,
+                        locked: false,
+                        game: GameKind::default(),
+                        rating: None,
+                    },
+                    password: None,
+                },
+                ctx,
+            );
+            let room_id = match self.sessions.get(&leader.id).and_then(|session| session.room) {
+                Some(room_id) => room_id,
+                None => {
+                    // The room was refused, and told the leader why.
+                    self.send_match(leader.id, MatchStatus::Cancelled);
+                    retry.extend(rest);
+                    continue;
+                }
+            };
+            if let Some(room) = self.rooms.get_mut(&room_id) {
+                room.criteria = leader.criteria.clone();
+            }
+            self.send_match(leader.id, MatchStatus::Matched { room: room_id });
+            for ticket in rest {
+                if self.enter(ticket.id, room_id) {
+                    self.send_match(ticket.id, MatchStatus::Matched { room: room_id });
+                } else {
+                    retry.push(ticket);
+                }
+            }
+        }
+        for ticket in retry {
+            self.matchmaker.requeue(ticket);
+        }
+    }
+
+    /// Starts a room actor with `owner` as its only player.
+    fn open_room(
+        &mut self,
+        owner: UserId,
+        mut brief: RoomBrief,
+        password: Option<String>,
+        criteria: MatchCriteria,
+        chat_log: ChatLog,
+        ctx: &mut Context<Self>,
+    ) {
+        let session = match self.sessions.get_mut(&owner) {
+            Some(session) => session,
+            None => return,
+        };
+        let room_id = brief.id;
+        session.room = Some(room_id);
+        brief.owner = owner;
+        brief.members = 1;
+        brief.playing = false;
+        let game = play::new_game(brief.game, &self.dictionary);
+        let room = Room::new(
+            room_id,
+            &brief,
+            session.clone(),
+            chat_log,
+            game,
+            ctx.address(),
+        );
+        let _ = session.pipe.do_send(Message::ReadyJoin {
+            room: (&room).into(),
+        });
+        info!("User {} opened room #{}", session.name, room_id);
+        let arbiter = &self.arbiters[self.next_arbiter];
+        self.next_arbiter = (self.next_arbiter + 1) % self.arbiters.len();
+        let addr = Room::start_in_arbiter(arbiter, move |_| room);
+        let _ = session.control.do_send(Control::Enter(addr.clone()));
+        for session in self.sessions.values() {
+            let _ = session.pipe.do_send(Message::NewRoom {
+                room: brief.clone(),
+            });
+        }
+        self.rooms.insert(
+            room_id,
+            RoomEntry {
+                addr,
+                brief,
+                members: vec![owner],
+                spectators: vec![],
+                bots: BTreeSet::new(),
+                criteria,
+                password,
+                restored_owner: None,
+                awaited: vec![],
+                bracket: None,
+            },
+        );
+        self.notify_presence(owner);
+    }
+
+    /// Seats a lobby session as a player once access has been granted,
+    /// telling whether they got in.
+    fn enter(&mut self, id: UserId, room_id: RoomId) -> bool {
+        let refusal = self
+            .rooms
+            .get(&room_id)
+            .and_then(|room| self.rating_refusal(id, &room.brief));
+        if let Some(session) = self.sessions.get_mut(&id) {
+            if session.room.is_some() {
+                return false;
+            }
+            if let Some(room) = self.rooms.get_mut(&room_id) {
+                if room.members.len() >= room.brief.capacity {
+                    let _ = session.pipe.do_send(Message::Alert(Alert::RoomFull));
+                    return false;
+                }
+                if room.brief.playing && !room.awaited.contains(&id) {
+                    let _ = session.pipe.do_send(Message::Alert(Alert::GameInProgress));
+                    return false;
+                }
+                if let Some(alert) = refusal {
+                    let _ = session.pipe.do_send(Message::Alert(alert));
+                    return false;
+                }
+                session.room = Some(room_id);
+                room.members.push(id);
+                room.awaited.retain(|&player| player != id);
+                room.refresh();
+                room.addr.do_send(room::Enter {
+                    session: session.clone(),
+                    spectator: false,
+                });
+                let _ = session.control.do_send(Control::Enter(room.addr.clone()));
+            } else {
+                return false;
+            }
+        } else {
+            return false;
+        }
+        self.broadcast_room(room_id);
+        self.notify_presence(id);
+        true
+    }
+
+    fn alert(&self, id: UserId, alert: Alert) {
+        if let Some(session) = self.sessions.get(&id) {
+            let _ = session.pipe.do_send(Message::Alert(alert));
+        }
+    }
+
+    /// Writes out every store, whether or not it changed.
+    fn persist(&self) {
+        self.accounts.save();
+        self.reports.save();
+        self.sanctions.save();
+    }
+
+    /// Waits for the stores saving in the background, before the process
+    /// ends.
+    fn flush(&self) {
+        self.accounts.flush();
+        self.ratings.flush();
+        self.writer.flush();
+    }
+
+    /// Hands the room actor the host's current copy of a session.
+    fn sync_room(&self, id: UserId) {
+        if let Some(session) = self.sessions.get(&id) {
+            if let Some(room) = session.room.and_then(|room| self.rooms.get(&room)) {
+                room.addr.do_send(room::Refresh {
+                    session: session.clone(),
+                });
+            }
+        }
+    }
+
+    fn expire_invite(&mut self, user: UserId, room: RoomId) {
+        if let Some(invite) = self.invites.remove(&(user, room)) {
+            if let Some(session) = self.sessions.get(&user) {
+                let _ = session.pipe.do_send(Message::InviteExpired { room });
+            }
+            self.alert(invite.from, Alert::InviteExpired { user });
+        }
+    }
+
+    /// Forgets every invite addressed to a user without telling anyone.
+    fn drop_invites(&mut self, user: UserId, ctx: &mut Context<Self>) {
+        let keys = self
+            .invites
+            .keys()
+            .filter(|&&(target, _)| target == user)
+            .cloned()
+            .collect::<Vec<_>>();
+        for key in keys {
+            if let Some(invite) = self.invites.remove(&key) {
+                ctx.cancel_future(invite.expiry);
+            }
+        }
+    }
+
+    fn broadcast_room(&self, room: RoomId) {
+        if let Some(room) = self.rooms.get(&room) {
+            for session in self.sessions.values() {
+                let _ = session.pipe.do_send(Message::RoomChanged {
+                    room: room.brief.clone(),
+                });
+            }
+        }
+    }
+}
+
+impl Host {
+    pub fn new(
+        accounts: Accounts,
+        reports: Reports,
+        sanctions: Sanctions,
+        audit: AuditLog,
+        matches: Matches,
+        ratings: Ratings,
+        dictionary: Dictionary,
+    ) -> Self {
+        Self {
+            sessions: HashMap::new(),
+            rng: rand::thread_rng(),
+            rooms: HashMap::new(),
+            arbiters: vec![],
+            next_arbiter: 0,
+            matchmaker: Matchmaker::default(),
+            invites: HashMap::new(),
+            accounts,
+            chat_log: ChatLog::default(),
+            reports,
+            sanctions,
+            audit,
+            matches,
+            ratings,
+            maintenance: None,
+            resumes: HashMap::new(),
+            restored: HashMap::new(),
+            next_connection: 0,
+            dictionary: Arc::new(dictionary),
+            writer: Writer::start(),
+            attempts: Throttle::new(&ratelimit::LOGIN),
+            tournaments: BTreeMap::new(),
+            next_tournament: 0,
+        }
+    }
+}
+
+impl Actor for Host {
+    type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        self.arbiters = (0..ROOM_ARBITERS).map(|_| Arbiter::new()).collect();
+        signal::ProcessSignals::from_registry()
+            .do_send(signal::Subscribe(ctx.address().recipient()));
+        ctx.run_interval(matchmaking::MATCH_INTERVAL, |host, ctx| {
+            host.run_matchmaking(ctx);
+        });
+        ctx.run_interval(snapshot::SNAPSHOT_INTERVAL, |host, ctx| {
+            // Once maintenance has sent everyone away there is nothing to keep.
+            if host.maintenance_left() != Some(0) {
+                host.save_snapshot(ctx, |_, _| ());
+            }
+        });
+        ctx.run_interval(tournament::TOURNAMENT_INTERVAL, |host, ctx| {
+            host.expire_tournaments();
+            host.open_matches(ctx);
+        });
+        ctx.run_later(snapshot::RESUME_TIMEOUT, |host, _| host.forget_restored());
+    }
+}
+
+impl Handler<Connect> for Host {
+    type Result = Welcome;
+
+    fn handle(&mut self, message: Connect, ctx: &mut Context<Self>) -> Self::Result {
+        let account = message
+            .token
+            .and_then(|token| self.accounts.by_token(&token))
+            .map(|account| (account.id, account.name.clone(), account.blocked.clone()));
+        let automated = account
+            .as_ref()
+            .and_then(|&(id, _, _)| self.accounts.get(id))
+            .map_or(false, |account| account.bot);
+        let resumed = self.take_resume(
+            account.as_ref().map(|(id, _, _)| *id),
+            message.resume.as_ref().map(String::as_str),
+        );
+        let (id, name, blocked) = match (account, &resumed) {
+            (Some(account), _) => account,
+            (None, Some(resumed)) => (resumed.id, resumed.name.clone(), BTreeSet::new()),
+            (None, None) => {
+                let (id, name) = self.generate_guest();
+                (id, name, BTreeSet::new())
+            }
+        };
+        let resume = match &resumed {
+            Some(resumed) => resumed.token.clone(),
+            None => (&mut self.rng)
+                .sample_iter(&Alphanumeric)
+                .take(RESUME_LEN)
+                .collect(),
+        };
+        // The newest login wins; the old tab is told why it lost.
+        if self.sessions.contains_key(&id) {
+            info!("User {} logged in again", name);
+            self.close_session(id, DisconnectReason::DuplicateLogin, ctx);
+        }
+        self.next_connection += 1;
+        let connection = self.next_connection;
+        info!("User {} joined", name);
+        let _ = message.addr.do_send(Message::Welcome {
+            id,
+            resume: resume.clone(),
+            users: self.sessions.values().map(Session::brief).collect(),
+            rooms: self.query_rooms(RoomQuery::default()),
+            games: play::available(&self.dictionary),
+        });
+        self.sessions.insert(
+            id,
+            Session {
+                id,
+                name: name.clone(),
+                pipe: message.addr,
+                control: message.control,
+                room: None,
+                spectating: false,
+                blocked,
+                ip: message.ip,
+                muted: None,
+                resume,
+                connection,
+                bot: false,
+                automated,
+            },
+        );
+        self.refresh_mute(id);
+        for (_, session) in self.sessions.iter() {
+            let _ = session.pipe.do_send(Message::Connected {
+                user: UserBrief {
+                    id,
+                    name: name.clone(),
+                    bot: automated,
+                },
+            });
+        }
+        self.send_friends(id);
+        self.send_blocked(id);
+        self.send_tournaments(id);
+        self.notify_presence(id);
+        if let Some(resumed) = resumed {
+            self.reclaim(&resumed, ctx);
+        }
+        Welcome {
+            id,
+            connection,
+            bot: automated,
+        }
+    }
+}
+
+impl Handler<Disconnect> for Host {
+    type Result = ();
+
+    fn handle(&mut self, message: Disconnect, ctx: &mut Context<Self>) {
+        let current = self
+            .sessions
+            .get(&message.id)
+            .map_or(false, |session| session.connection == message.connection);
+        if current {
+            self.drop_session(message.id, ctx);
+        }
+    }
+}
+
+impl Host {
+    /// Closes the user's connection with `reason` and forgets the session.
+    fn close_session(&mut self, id: UserId, reason: DisconnectReason, ctx: &mut Context<Self>) {
+        if let Some(session) = self.sessions.get(&id) {
+            let _ = session.control.do_send(Control::Close(reason));
+        }
+        self.drop_session(id, ctx);
+    }
+
+    fn drop_session(&mut self, id: UserId, ctx: &mut Context<Self>) {
+        self.matchmaker.cancel(id);
+        self.drop_invites(id, ctx);
+        self.handle(QuitRoom {
+            id
+        }, ctx);
+        if let Some(session) = self.sessions.get(&id) {
+            info!("User {} quit", session.name);
+            self.sessions.remove(&id);
+            for (_, session) in self.sessions.iter() {
+                let _ = session.pipe.do_send(Message::Disconnected { id });
+            }
+            self.notify_presence(id);
+        }
+    }
+}
+
+impl Handler<Chat> for Host {
+    type Result = ();
+
+    fn handle(&mut self, message: Chat, _: &mut Context<Self>) {
+        let text = message.text.trim().to_string();
+        if text.len() < 1 {
+            return;
+        }
+        let from = message.id;
+        if self.sanctioned(from, SanctionKind::Mute) {
+            return;
+        }
+        if let Some(to) = message.to {
+            // Being blocked looks the same as the target being away.
+            let target = self
+                .sessions
+                .get(&to)
+                .filter(|session| !session.blocked.contains(&from));
+            if let Some(session) = target {
+                let id = self
+                    .chat_log
+                    .push(from, Some(to), None, &text, ChatScope::Whisper);
+                let _ = session.pipe.do_send(Message::Chat {
+                    id,
+                    from,
+                    text,
+                    scope: ChatScope::Whisper,
+                });
+            } else if let Some(from_session) = self.sessions.get(&from) {
+                let _ = from_session
+                    .pipe
+                    .do_send(Message::Alert(Alert::TargetNotFound));
+            }
+        } else {
+            if let Some(room) = self
+                .sessions
+                .get(&from)
+                .and_then(|session| session.room)
+                .and_then(|room| self.rooms.get(&room))
+            {
+                // The session has not picked up its route yet.
+                room.addr.do_send(Chat {
+                    id: from,
+                    text,
+                    to: None,
+                    team: message.team,
+                });
+                return;
+            }
+            let id = self
+                .chat_log
+                .push(from, None, None, &text, ChatScope::Public);
+            for session in self.sessions.values() {
+                if session.blocked.contains(&from) {
+                    continue;
+                }
+                let _ = session.pipe.do_send(Message::Chat {
+                    id,
+                    from,
+                    text: text.clone(),
+                    scope: ChatScope::Public,
+                });
+            }
+        }
+    }
+}
+
+const ROOM_LIMIT: RoomId = 1000;
+const MIN_CAPACITY: usize = 2;
+const MAX_CAPACITY: usize = 8;
+
+/// A room password as it is stored and compared, blank being none.
+fn room_password(password: Option<String>) -> Option<String> {
+    password
+        .map(|password| password.trim().to_string())
+        .filter(|password| password.len() > 0)
+}
+
+impl Handler<CreateRoom> for Host {
+    type Result = ();
+
+    fn handle(&mut self, message: CreateRoom, ctx: &mut Context<Self>) {
+        if self.in_maintenance(message.id) || self.sanctioned(message.id, SanctionKind::NoRooms) {
+            return;
+        }
+        self.cancel_match(message.id);
+        match self.sessions.get(&message.id) {
+            // Creating a room in a room?!
+            Some(session) if session.room.is_none() => (),
+            _ => return,
+        }
+        let title = message.room.title.trim().to_string();
+        if title.len() < 1 {
+            return;
+        }
+        // Ids of rooms waiting to be restored are taken as well.
+        if self.rooms.len() + self.restored.len() >= ROOM_LIMIT as usize {
+            return;
+        }
+        let room_id = loop {
+            let room_id = self.rng.gen_range(0, ROOM_LIMIT);
+            if !self.rooms.contains_key(&room_id) && !self.restored.contains_key(&room_id) {
+                break room_id;
+            }
+        };
+        let game = message.room.game.game_type();
+        if !play::available(&self.dictionary).contains(&game) {
+            self.alert(message.id, Alert::GameUnavailable);
+            return;
+        }
+        let password = room_password(message.password);
+        let capacity = match message.room.capacity {
+            0 => MAX_CAPACITY,
+            capacity => capacity.max(MIN_CAPACITY).min(MAX_CAPACITY),
+        };
+        let brief = RoomBrief {
+            id: room_id,
+            title,
+            owner: message.id,
+            members: 1,
+            capacity,
+            playing: false,
+            separate_spectators: message.room.separate_spectators,
+            locked: password.is_some(),
+            game: play::sanitize(message.room.game),
+            rating: message.room.rating.filter(|range| range.min <= range.max),
+        };
+        if let Some(alert) = self.rating_refusal(message.id, &brief) {
+            self.alert(message.id, alert);
+            return;
+        }
+        self.open_room(
+            message.id,
+            brief,
+            password,
+            MatchCriteria::default(),
+            ChatLog::default(),
+            ctx,
+        );
+    }
+}
+
+impl Handler<GetRoomDetail> for Host {
+    type Result = ();
+
+    fn handle(&mut self, message: GetRoomDetail, _: &mut Context<Self>) {
+        if let Some(session) = self.sessions.get(&message.id) {
+            if let Some(room) = self.rooms.get(&message.room) {
+                room.addr.do_send(room::Detail {
+                    pipe: session.pipe.clone(),
+                });
+            }
+        }
+    }
+}
+
+impl Handler<room::Playing> for Host {
+    type Result = ();
+
+    fn handle(&mut self, message: room::Playing, _: &mut Context<Self>) {
+        if let Some(room) = self.rooms.get_mut(&message.room) {
+            room.brief.playing = message.playing;
+        }
+        self.broadcast_room(message.room);
+    }
+}
+
+impl Handler<QueryRooms> for Host {
+    type Result = ();
+
+    fn handle(&mut self, message: QueryRooms, _: &mut Context<Self>) {
+        if let Some(session) = self.sessions.get(&message.id) {
+            let _ = session.pipe.do_send(Message::RoomPage {
+                page: self.query_rooms(message.query),
+            });
+        }
+    }
+}
+
+impl Handler<JoinRoom> for Host {
+    type Result = ();
+
+    fn handle(&mut self, message: JoinRoom, _: &mut Context<Self>) {
+        self.cancel_match(message.id);
+        let password = room_password(message.password);
+        let denied = match self.rooms.get(&message.room) {
+            Some(room) => room.password.is_some() && room.password != password,
+            None => return,
+        };
+        if denied {
+            self.alert(message.id, Alert::WrongPassword);
+            return;
+        }
+        self.enter(message.id, message.room);
+    }
+}
+
+impl Handler<QuitRoom> for Host {
+    type Result = ();
+
+    fn handle(&mut self, message: QuitRoom, ctx: &mut Context<Self>) {
+        if let Some(session) = self.sessions.get_mut(&message.id) {
+            let bot = session.bot;
+            let room_id = match session.room.take() {
+                Some(room_id) => room_id,
+                None => return,
+            };
+            let spectating = std::mem::replace(&mut session.spectating, false);
+            let _ = session.control.do_send(Control::Leave);
+            let mut to_destroy = None;
+            if let Some(room) = self.rooms.get_mut(&room_id) {
+                if spectating {
+                    room.spectators.retain(|&spectator| spectator != message.id);
+                } else {
+                    room.members.retain(|&member| member != message.id);
+                    room.bots.remove(&message.id);
+                    room.refresh();
+                }
+                room.addr.do_send(room::Leave { id: message.id });
+                if room.members.len() < 1 {
+                    to_destroy = Some(room_id);
+                }
+            }
+            if let Some(room) = to_destroy.and_then(|room_id| self.rooms.remove(&room_id)) {
+                for spectator in room.spectators.iter() {
+                    if let Some(session) = self.sessions.get_mut(spectator) {
+                        session.room = None;
+                        session.spectating = false;
+                        let _ = session.control.do_send(Control::Leave);
+                    }
+                }
+                for session in self.sessions.values() {
+                    let _ = session.pipe.do_send(Message::DestroyRoom {
+                        room: room_id,
+                    });
+                }
+                for &spectator in room.spectators.iter() {
+                    self.notify_presence(spectator);
+                }
+            } else if !spectating {
+                self.broadcast_room(room_id);
+            }
+            self.notify_presence(message.id);
+            if !bot && !spectating {
+                self.dismiss_bots(room_id, ctx);
+            }
+        }
+    }
+}
+
+impl Handler<QuickJoin> for Host {
+    type Result = ();
+
+    fn handle(&mut self, message: QuickJoin, ctx: &mut Context<Self>) {
+        match self.sessions.get(&message.id) {
+            Some(session) if session.room.is_none() => (),
+            _ => return,
+        }
+        self.matchmaker.enqueue(message.id, message.criteria);
+        self.send_match(
+            message.id,
+            MatchStatus::Queued {
+                estimated_wait: self.matchmaker.estimate().as_secs(),
+            },
+        );
+        self.run_matchmaking(ctx);
+    }
+}
+
+impl Handler<CancelQuickJoin> for Host {
+    type Result = ();
+
+    fn handle(&mut self, message: CancelQuickJoin, _: &mut Context<Self>) {
+        self.cancel_match(message.id);
+    }
+}
+
+impl Handler<Spectate> for Host {
+    type Result = ();
+
+    fn handle(&mut self, message: Spectate, _: &mut Context<Self>) {
+        self.cancel_match(message.id);
+        if let Some(session) = self.sessions.get_mut(&message.id) {
+            if session.room.is_some() {
+                return;
+            }
+            if let Some(room) = self.rooms.get_mut(&message.room) {
+                if room.password.is_some() {
+                    let _ = session.pipe.do_send(Message::Alert(Alert::WrongPassword));
+                    return;
+                }
+                session.room = Some(message.room);
+                session.spectating = true;
+                room.spectators.push(message.id);
+                room.addr.do_send(room::Enter {
+                    session: session.clone(),
+                    spectator: true,
+                });
+                let _ = session.control.do_send(Control::Enter(room.addr.clone()));
+            } else {
+                return;
+            }
+        }
+        self.notify_presence(message.id);
+    }
+}
+
+impl Handler<InviteToRoom> for Host {
+    type Result = ();
+
+    fn handle(&mut self, message: InviteToRoom, ctx: &mut Context<Self>) {
+        let room_id = match self.sessions.get(&message.id) {
+            Some(session) if !session.spectating => match session.room {
+                Some(room_id) => room_id,
+                None => return,
+            },
+            _ => return,
+        };
+        let available = message.user != message.id
+            && self.sessions.get(&message.user).map_or(false, |session| {
+                session.room.is_none() && !session.blocked.contains(&message.id)
+            });
+        if !available {
+            self.alert(message.id, Alert::InviteUnavailable);
+            return;
+        }
+        let brief = match self.rooms.get(&room_id) {
+            Some(room) => room.brief.clone(),
+            None => return,
+        };
+        let (user, from) = (message.user, message.id);
+        let expiry = ctx.run_later(INVITE_TIMEOUT, move |host, _| {
+            host.expire_invite(user, room_id);
+        });
+        if let Some(previous) = self.invites.insert((user, room_id), Invite { from, expiry }) {
+            ctx.cancel_future(previous.expiry);
+        }
+        if let Some(session) = self.sessions.get(&user) {
+            let _ = session.pipe.do_send(Message::Invited { from, room: brief });
+        }
+        self.alert(from, Alert::InviteSent { user });
+    }
+}
+
+impl Handler<AnswerInvite> for Host {
+    type Result = ();
+
+    fn handle(&mut self, message: AnswerInvite, ctx: &mut Context<Self>) {
+        let invite = match self.invites.remove(&(message.id, message.room)) {
+            Some(invite) => invite,
+            None => return,
+        };
+        ctx.cancel_future(invite.expiry);
+        if message.accept {
+            // An invite stands in for the room password.
+            self.cancel_match(message.id);
+            self.enter(message.id, message.room);
+        } else {
+            self.alert(invite.from, Alert::InviteDeclined { user: message.id });
+        }
+    }
+}
+
+fn login(token: String, account: &crate::account::Account) -> Login {
+    Login {
+        token,
+        user: UserBrief {
+            id: account.id,
+            name: account.name.clone(),
+            bot: account.bot,
+        },
+    }
+}
+
+impl Host {
+    /// Counts a login or sign-up against its address and, if given, the
+    /// name it is for.
+    fn attempt(&mut self, ip: Option<String>, name: Option<&str>) -> Result<(), AuthError> {
+        let now = Instant::now();
+        let keys = ip
+            .map(|ip| format!("ip {}", ip))
+            .into_iter()
+            .chain(name.map(|name| format!("name {}", name.trim().to_lowercase())));
+        let mut allowed = true;
+        for key in keys {
+            allowed &= self.attempts.take(key, now);
+        }
+        if allowed {
+            Ok(())
+        } else {
+            Err(AuthError::TooManyAttempts)
+        }
+    }
+}
+
+impl Handler<CheckRegister> for Host {
+    type Result = Result<(), AuthError>;
+
+    fn handle(&mut self, message: CheckRegister, _: &mut Context<Self>) -> Self::Result {
+        self.attempt(message.ip, None)?;
+        self.accounts.check_name(&message.name)
+    }
+}
+
+impl Handler<Register> for Host {
+    type Result = Result<Login, AuthError>;
+
+    fn handle(&mut self, message: Register, _: &mut Context<Self>) -> Self::Result {
+        let (token, account) = self.accounts.register(&message.name, message.secret)?;
+        info!("Account {} registered", account.name);
+        Ok(login(token, account))
+    }
+}
+
+impl Handler<LogIn> for Host {
+    type Result = Result<Check, AuthError>;
+
+    fn handle(&mut self, message: LogIn, _: &mut Context<Self>) -> Self::Result {
+        self.attempt(message.ip, Some(&message.name))?;
+        self.accounts.check(&message.name)
+    }
+}
+
+impl Handler<Verified> for Host {
+    type Result = Result<Login, AuthError>;
+
+    fn handle(&mut self, message: Verified, _: &mut Context<Self>) -> Self::Result {
+        let (token, account) = self.accounts.login(message.check, message.upgrade)?;
+        Ok(login(token, account))
+    }
+}
+
+impl Handler<CreateBot> for Host {
+    type Result = Result<Login, AuthError>;
+
+    fn handle(&mut self, message: CreateBot, _: &mut Context<Self>) -> Self::Result {
+        let (token, account) = self.accounts.create_bot(&message.token, &message.name)?;
+        let owner = account.owner.unwrap_or_default();
+        info!("Bot account {} created by {}", account.name, owner);
+        Ok(login(token, account))
+    }
+}
+
+impl Handler<RotateBotToken> for Host {
+    type Result = Result<Login, AuthError>;
+
+    fn handle(&mut self, message: RotateBotToken, ctx: &mut Context<Self>) -> Self::Result {
+        let (token, account) = self.accounts.rotate_bot(&message.token, message.bot)?;
+        let login = login(token, account);
+        info!("Bot account {} got a new token", login.user.name);
+        // Whoever held the old token is signed in no longer.
+        self.close_session(message.bot, DisconnectReason::Kicked, ctx);
+        Ok(login)
+    }
+}
+
+impl Handler<RevokeBotToken> for Host {
+    type Result = Result<(), AuthError>;
+
+    fn handle(&mut self, message: RevokeBotToken, ctx: &mut Context<Self>) -> Self::Result {
+        self.accounts.revoke_bot(&message.token, message.bot)?;
+        info!("Bot account {} had its token revoked", message.bot);
+        self.close_session(message.bot, DisconnectReason::Kicked, ctx);
+        Ok(())
+    }
+}
@@ -0,0 +1,353 @@
+use super::room::ChatContext;
+use super::{unix_time, Host};
+use actix::prelude::*;
+use bridge::api::{
+    self, AdminError, AuditAction, AuditEntry, ChatRecord, NewSanction, Resolution, Resolve,
+    Sanction, SanctionTarget,
+};
+use bridge::*;
+use log::info;
+
+#[derive(Message)]
+pub struct Report {
+    pub id: UserId,
+    pub user: UserId,
+    pub reason: String,
+    pub message_id: ChatId,
+}
+
+#[derive(Message)]
+#[rtype(result = "Result<Vec<api::Report>, AdminError>")]
+pub struct ListReports {
+    pub token: String,
+    /// Include reports that were already resolved.
+    pub all: bool,
+}
+
+#[derive(Message)]
+#[rtype(result = "Result<(), AdminError>")]
+pub struct ResolveReport {
+    pub token: String,
+    pub report: ReportId,
+    pub resolve: Resolve,
+}
+
+/// Asked before a connection is upgraded to a session. A refusal comes
+/// with the alert that explains it.
+#[derive(Message)]
+#[rtype(result = "Result<(), Alert>")]
+pub struct Admit {
+    pub token: Option<String>,
+    pub ip: Option<String>,
+}
+
+#[derive(Message)]
+#[rtype(result = "Result<Vec<Sanction>, AdminError>")]
+pub struct ListSanctions {
+    pub token: String,
+    /// Include lifted and expired sanctions.
+    pub all: bool,
+}
+
+#[derive(Message)]
+#[rtype(result = "Result<Sanction, AdminError>")]
+pub struct AddSanction {
+    pub token: String,
+    pub sanction: NewSanction,
+}
+
+#[derive(Message)]
+#[rtype(result = "Result<(), AdminError>")]
+pub struct LiftSanction {
+    pub token: String,
+    pub sanction: SanctionId,
+}
+
+/// Drops a user's connection without any lasting sanction.
+#[derive(Message)]
+#[rtype(result = "Result<(), AdminError>")]
+pub struct Kick {
+    pub token: String,
+    pub user: UserId,
+}
+
+#[derive(Message)]
+#[rtype(result = "Result<Vec<AuditEntry>, AdminError>")]
+pub struct ListAudit {
+    pub token: String,
+}
+
+fn sanction_alert(sanction: &Sanction) -> Alert {
+    Alert::Sanctioned {
+        kind: sanction.kind,
+        reason: sanction.reason.clone(),
+        until: sanction.until,
+    }
+}
+
+impl Host {
+    fn sanction(&self, id: UserId, kind: SanctionKind) -> Option<&Sanction> {
+        let account = self.accounts.get(id).map(|account| account.id);
+        let ip = self
+            .sessions
+            .get(&id)
+            .and_then(|session| session.ip.as_ref())
+            .map(String::as_str);
+        self.sanctions.find(kind, account, ip, unix_time())
+    }
+
+    /// Tells the user off when a sanction forbids `kind`.
+    pub(super) fn sanctioned(&self, id: UserId, kind: SanctionKind) -> bool {
+        match self.sanction(id, kind) {
+            Some(sanction) => {
+                self.alert(id, sanction_alert(sanction));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Recomputes the mute a session carries into its room.
+    pub(super) fn refresh_mute(&mut self, id: UserId) {
+        let muted = self.sanction(id, SanctionKind::Mute).cloned();
+        if let Some(session) = self.sessions.get_mut(&id) {
+            session.muted = muted;
+        }
+        self.sync_room(id);
+    }
+
+    fn targets(&self, target: &SanctionTarget) -> Vec<UserId> {
+        self.sessions
+            .values()
+            .filter(|session| match target {
+                SanctionTarget::Account(id) => session.id == *id,
+                SanctionTarget::Ip(ip) => session.ip.as_ref() == Some(ip),
+            })
+            .map(|session| session.id)
+            .collect()
+    }
+
+    pub(super) fn admin(&self, token: &str) -> Result<UserId, AdminError> {
+        self.accounts
+            .admin(token)
+            .map(|account| account.id)
+            .ok_or(AdminError::Unauthorized)
+    }
+
+    fn file_report(&mut self, report: Report, context: Vec<ChatRecord>) {
+        let Report {
+            id,
+            user,
+            reason,
+            message_id,
+        } = report;
+        // The line has to be one the reported user wrote, and still
+        // remembered.
+        let line = context.iter().find(|record| record.id == message_id);
+        if line.map_or(true, |record| record.from != user) {
+            return;
+        }
+        if self.reports.filed(id, message_id) {
+            return;
+        }
+        let report = self.reports.file(api::Report {
+            id: 0,
+            reporter: id,
+            user,
+            reason,
+            message_id,
+            context,
+            time: unix_time(),
+            resolution: None,
+        });
+        info!("User {} reported {} (report #{})", id, user, report);
+        self.alert(id, Alert::ReportReceived);
+    }
+}
+
+impl Handler<Report> for Host {
+    type Result = ();
+
+    fn handle(&mut self, message: Report, ctx: &mut Context<Self>) {
+        let reason = message.reason.trim();
+        if reason.len() < 1 || message.id == message.user {
+            return;
+        }
+        let message = Report {
+            reason: reason.to_string(),
+            ..message
+        };
+        if let Some(context) = self.chat_log.context(message.message_id, message.id) {
+            self.file_report(message, context);
+            return;
+        }
+        // Not a lobby line, so it may still be in the reporter's room.
+        let room = self
+            .sessions
+            .get(&message.id)
+            .and_then(|session| session.room)
+            .and_then(|room| self.rooms.get(&room));
+        match room {
+            Some(room) => {
+                room.addr
+                    .send(ChatContext {
+                        message_id: message.message_id,
+                        viewer: message.id,
+                    })
+                    .into_actor(self)
+                    .then(|result, host, _| {
+                        let context = result.ok().and_then(|context| context);
+                        host.file_report(message, context.unwrap_or_default());
+                        fut::ok(())
+                    })
+                    .spawn(ctx);
+            }
+            None => self.file_report(message, vec![]),
+        }
+    }
+}
+
+impl Handler<ListReports> for Host {
+    type Result = Result<Vec<api::Report>, AdminError>;
+
+    fn handle(&mut self, message: ListReports, _: &mut Context<Self>) -> Self::Result {
+        self.admin(&message.token)?;
+        Ok(self.reports.list(message.all))
+    }
+}
+
+impl Handler<ResolveReport> for Host {
+    type Result = Result<(), AdminError>;
+
+    fn handle(&mut self, message: ResolveReport, _: &mut Context<Self>) -> Self::Result {
+        let by = self.admin(&message.token)?;
+        let resolution = Resolution {
+            by,
+            note: message.resolve.note,
+            time: unix_time(),
+        };
+        if self.reports.resolve(message.report, resolution) {
+            info!("Report #{} resolved by {}", message.report, by);
+            self.audit
+                .record(by, unix_time(), AuditAction::ResolvedReport(message.report));
+            Ok(())
+        } else {
+            Err(AdminError::NotFound)
+        }
+    }
+}
+
+impl Handler<Admit> for Host {
+    type Result = Result<(), Alert>;
+
+    fn handle(&mut self, message: Admit, _: &mut Context<Self>) -> Self::Result {
+        if let Some(seconds) = self.maintenance_left() {
+            return Err(Alert::Maintenance { seconds });
+        }
+        let account = message
+            .token
+            .and_then(|token| self.accounts.by_token(&token))
+            .map(|account| account.id);
+        let ban = self.sanctions.find(
+            SanctionKind::Ban,
+            account,
+            message.ip.as_ref().map(String::as_str),
+            unix_time(),
+        );
+        match ban {
+            Some(sanction) => Err(sanction_alert(sanction)),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Handler<ListSanctions> for Host {
+    type Result = Result<Vec<Sanction>, AdminError>;
+
+    fn handle(&mut self, message: ListSanctions, _: &mut Context<Self>) -> Self::Result {
+        self.admin(&message.token)?;
+        Ok(self.sanctions.list(message.all, unix_time()))
+    }
+}
+
+impl Handler<AddSanction> for Host {
+    type Result = Result<Sanction, AdminError>;
+
+    fn handle(&mut self, message: AddSanction, ctx: &mut Context<Self>) -> Self::Result {
+        let by = self.admin(&message.token)?;
+        let NewSanction {
+            target,
+            kind,
+            reason,
+            duration,
+        } = message.sanction;
+        let time = unix_time();
+        let sanction = self.sanctions.add(Sanction {
+            id: 0,
+            target,
+            kind,
+            reason,
+            by,
+            time,
+            until: time.saturating_add(duration),
+            lifted: false,
+        });
+        info!("{:?} on {:?} by {}", sanction.kind, sanction.target, by);
+        self.audit
+            .record(by, time, AuditAction::Sanctioned(sanction.clone()));
+        for id in self.targets(&sanction.target) {
+            self.alert(id, sanction_alert(&sanction));
+            match sanction.kind {
+                SanctionKind::Ban => self.close_session(id, DisconnectReason::Banned, ctx),
+                SanctionKind::Mute => self.refresh_mute(id),
+                SanctionKind::NoRooms => (),
+            }
+        }
+        Ok(sanction)
+    }
+}
+
+impl Handler<LiftSanction> for Host {
+    type Result = Result<(), AdminError>;
+
+    fn handle(&mut self, message: LiftSanction, _: &mut Context<Self>) -> Self::Result {
+        let by = self.admin(&message.token)?;
+        let sanction = self
+            .sanctions
+            .lift(message.sanction)
+            .ok_or(AdminError::NotFound)?;
+        info!("Sanction #{} lifted by {}", sanction.id, by);
+        self.audit
+            .record(by, unix_time(), AuditAction::Lifted(sanction.id));
+        for id in self.targets(&sanction.target) {
+            self.refresh_mute(id);
+        }
+        Ok(())
+    }
+}
+
+impl Handler<Kick> for Host {
+    type Result = Result<(), AdminError>;
+
+    fn handle(&mut self, message: Kick, ctx: &mut Context<Self>) -> Self::Result {
+        let by = self.admin(&message.token)?;
+        let user = message.user;
+        if !self.sessions.contains_key(&user) {
+            return Err(AdminError::NotFound);
+        }
+        info!("User {} kicked by {}", user, by);
+        self.audit
+            .record(by, unix_time(), AuditAction::Kicked(user));
+        self.close_session(user, DisconnectReason::Kicked, ctx);
+        Ok(())
+    }
+}
+
+impl Handler<ListAudit> for Host {
+    type Result = Result<Vec<AuditEntry>, AdminError>;
+
+    fn handle(&mut self, message: ListAudit, _: &mut Context<Self>) -> Self::Result {
+        self.admin(&message.token)?;
+        Ok(self.audit.entries().to_vec())
+    }
+}
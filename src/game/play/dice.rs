@@ -0,0 +1,119 @@
+use super::{deadline_in, time_left, Game, GameOutcome, SavedGame, Strategy};
+use bridge::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// Dice left unrolled by then are rolled for their players.
+const ROLL_TIME: Duration = Duration::from_secs(15);
+
+#[derive(Default)]
+pub(super) struct Dice {
+    rolls: Vec<(UserId, Option<u8>)>,
+    deadline: Option<Instant>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub(in super::super) struct Saved {
+    rolls: Vec<(UserId, Option<u8>)>,
+    left: Option<u64>,
+}
+
+fn roll() -> u8 {
+    rand::thread_rng().gen_range(1, 7)
+}
+
+impl Game for Dice {
+    fn setup(&mut self, players: &[UserId], now: Instant) -> Result<(), Alert> {
+        if players.len() < 2 {
+            return Err(Alert::NotEnoughPlayers);
+        }
+        self.rolls = players.iter().map(|&id| (id, None)).collect();
+        self.deadline = Some(now + ROLL_TIME);
+        Ok(())
+    }
+
+    fn action(&mut self, player: UserId, action: GameAction, _: Instant) -> Result<(), Alert> {
+        match action {
+            GameAction::Roll => match self.rolls.iter_mut().find(|(id, _)| *id == player) {
+                Some((_, rolled @ None)) => {
+                    *rolled = Some(roll());
+                    Ok(())
+                }
+                _ => Err(Alert::InvalidMove),
+            },
+            _ => Err(Alert::InvalidMove),
+        }
+    }
+
+    fn tick(&mut self, _: Instant) {
+        for (_, rolled) in self.rolls.iter_mut().filter(|(_, rolled)| rolled.is_none()) {
+            *rolled = Some(roll());
+        }
+        self.deadline = None;
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    fn leave(&mut self, player: UserId) {
+        self.rolls.retain(|&(id, _)| id != player);
+    }
+
+    fn view(&self, _: Option<UserId>) -> GameView {
+        GameView::Dice {
+            rolls: self.rolls.clone(),
+        }
+    }
+
+    fn save(&self, now: Instant) -> SavedGame {
+        SavedGame::Dice(Saved {
+            rolls: self.rolls.clone(),
+            left: time_left(self.deadline, now),
+        })
+    }
+
+    fn restore(&mut self, saved: SavedGame, now: Instant) -> bool {
+        match saved {
+            SavedGame::Dice(saved) => {
+                self.rolls = saved.rolls;
+                self.deadline = deadline_in(saved.left, now);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn outcome(&self) -> Option<GameOutcome> {
+        if self.rolls.iter().any(|(_, rolled)| rolled.is_none()) {
+            return None;
+        }
+        let best = self.rolls.iter().filter_map(|&(_, rolled)| rolled).max()?;
+        Some(GameOutcome {
+            winners: self
+                .rolls
+                .iter()
+                .filter(|&&(_, rolled)| rolled == Some(best))
+                .map(|&(id, _)| id)
+                .collect(),
+            scores: self
+                .rolls
+                .iter()
+                .filter_map(|&(id, rolled)| Some((id, u32::from(rolled?))))
+                .collect(),
+        })
+    }
+}
+
+/// Rolls as soon as it may.
+pub(super) struct DiceBot;
+
+impl Strategy for DiceBot {
+    fn act(&mut self, me: UserId, view: &GameView) -> Option<GameAction> {
+        match view {
+            GameView::Dice { rolls } if rolls.contains(&(me, None)) => Some(GameAction::Roll),
+            _ => None,
+        }
+    }
+}
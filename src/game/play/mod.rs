@@ -0,0 +1,129 @@
+use bridge::*;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+mod dice;
+mod wordchain;
+pub use wordchain::Dictionary;
+
+const MIN_TURN_SECONDS: u64 = 5;
+const MAX_TURN_SECONDS: u64 = 60;
+
+/// How a round ended. Players missing from `scores` scored nothing.
+pub(super) struct GameOutcome {
+    pub(super) winners: Vec<UserId>,
+    pub(super) scores: Vec<(UserId, u32)>,
+}
+
+/// A round in progress as a snapshot keeps it. Deadlines are kept as the
+/// milliseconds left on them, since the clock starts over with the process.
+#[derive(Serialize, Deserialize, Clone)]
+pub(super) enum SavedGame {
+    Dice(dice::Saved),
+    WordChain(wordchain::Saved),
+}
+
+fn time_left(deadline: Option<Instant>, now: Instant) -> Option<u64> {
+    deadline.map(|deadline| {
+        let left = if deadline > now {
+            deadline - now
+        } else {
+            Duration::from_secs(0)
+        };
+        left.as_secs() * 1000 + u64::from(left.subsec_millis())
+    })
+}
+
+fn deadline_in(left: Option<u64>, now: Instant) -> Option<Instant> {
+    left.map(|left| now + Duration::from_millis(left))
+}
+
+/// The rules of one game. The room hosting it owns the players, the clock
+/// and the broadcasting; a game only decides what happens.
+pub(super) trait Game: Send {
+    /// Starts a round with the room's players in seat order.
+    fn setup(&mut self, players: &[UserId], now: Instant) -> Result<(), Alert>;
+
+    fn action(&mut self, player: UserId, action: GameAction, now: Instant) -> Result<(), Alert>;
+
+    /// Called once `deadline` has passed.
+    fn tick(&mut self, now: Instant);
+
+    /// When the game next wants a `tick`, if at all.
+    fn deadline(&self) -> Option<Instant>;
+
+    /// A player walked out in the middle of a round.
+    fn leave(&mut self, player: UserId);
+
+    /// What `viewer` gets to see, `None` being a spectator.
+    fn view(&self, viewer: Option<UserId>) -> GameView;
+
+    /// Set once the round is over.
+    fn outcome(&self) -> Option<GameOutcome>;
+
+    /// The round in progress, for a snapshot.
+    fn save(&self, now: Instant) -> SavedGame;
+
+    /// Picks up a saved round where it stopped, refusing one of another
+    /// game.
+    fn restore(&mut self, saved: SavedGame, now: Instant) -> bool;
+
+    /// Drains what happened since the last call, for everyone to hear.
+    fn take_events(&mut self) -> Vec<GameEvent> {
+        vec![]
+    }
+}
+
+/// How a bot plays a game, seeing only what a player would.
+pub(super) trait Strategy: Send {
+    /// The move to make, if `me` has one to make in `view`.
+    fn act(&mut self, me: UserId, view: &GameView) -> Option<GameAction>;
+
+    /// Forgets what it learned in a round that has ended.
+    fn reset(&mut self) {}
+}
+
+/// The games the server can host with `dictionary`.
+pub(super) fn available(dictionary: &Dictionary) -> Vec<GameType> {
+    let mut games = vec![GameType::Dice];
+    if !dictionary.is_empty() {
+        games.push(GameType::WordChain);
+    }
+    games
+}
+
+/// Keeps the options a room creator picked within what the server allows.
+pub(super) fn sanitize(kind: GameKind) -> GameKind {
+    match kind {
+        GameKind::WordChain {
+            initial_sound_rule,
+            turn_seconds,
+        } => GameKind::WordChain {
+            initial_sound_rule,
+            turn_seconds: turn_seconds.max(MIN_TURN_SECONDS).min(MAX_TURN_SECONDS),
+        },
+        kind => kind,
+    }
+}
+
+pub(super) fn new_game(kind: GameKind, dictionary: &Arc<Dictionary>) -> Box<dyn Game> {
+    match kind {
+        GameKind::Dice => Box::new(dice::Dice::default()),
+        GameKind::WordChain {
+            initial_sound_rule,
+            turn_seconds,
+        } => Box::new(wordchain::WordChain::new(
+            dictionary.clone(),
+            initial_sound_rule,
+            turn_seconds,
+        )),
+    }
+}
+
+pub(super) fn new_strategy(kind: GameKind, dictionary: &Arc<Dictionary>) -> Box<dyn Strategy> {
+    match kind {
+        GameKind::Dice => Box::new(dice::DiceBot),
+        GameKind::WordChain { .. } => Box::new(wordchain::WordChainBot::new(dictionary.clone())),
+    }
+}
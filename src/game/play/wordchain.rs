@@ -0,0 +1,406 @@
+use super::{deadline_in, time_left, Game, GameOutcome, SavedGame, Strategy};
+use crate::storage;
+use bridge::*;
+use log::{info, warn};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const DICTIONARY_FILE: &str = "words.txt";
+const MIN_WORD_LEN: usize = 2;
+
+const HANGUL_FIRST: u32 = 0xAC00;
+const HANGUL_LAST: u32 = 0xD7A3;
+const NIEUN: u32 = 2;
+const RIEUL: u32 = 5;
+const IEUNG: u32 = 11;
+/// ㅑ ㅒ ㅕ ㅖ ㅛ ㅠ ㅣ, the vowels that drop ㄴ and ㄹ in 두음법칙.
+const Y_VOWELS: [u32; 7] = [2, 3, 6, 7, 12, 17, 20];
+
+/// Words the game accepts, one per line in `data/words.txt` under the
+/// directory the server runs in. The file isn't shipped; without it the
+/// server doesn't offer word chain at all.
+#[derive(Default)]
+pub struct Dictionary {
+    /// Sorted, so a lookup is a binary search.
+    words: Vec<String>,
+}
+
+impl Dictionary {
+    /// A missing file leaves the dictionary empty, which only keeps
+    /// word-chain rounds from starting.
+    pub fn load() -> io::Result<Self> {
+        let text = match fs::read_to_string(storage::path(DICTIONARY_FILE)) {
+            Ok(text) => text,
+            Err(ref e) if e.kind() == ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e),
+        };
+        let mut words = text
+            .lines()
+            .map(str::trim)
+            .filter(|word| word.chars().count() >= MIN_WORD_LEN)
+            .map(String::from)
+            .collect::<Vec<_>>();
+        words.sort();
+        words.dedup();
+        if words.is_empty() {
+            warn!("No words in data/{}, so word chain is off", DICTIONARY_FILE);
+        } else {
+            info!("Loaded {} words", words.len());
+        }
+        Ok(Self { words })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    fn contains(&self, word: &str) -> bool {
+        self.words
+            .binary_search_by(|probe| probe.as_str().cmp(word))
+            .is_ok()
+    }
+
+    /// A random word starting with one of `starts` that `allowed` takes.
+    fn pick(&self, starts: &[char], allowed: impl Fn(&str) -> bool) -> Option<&str> {
+        let mut candidates = vec![];
+        for start in starts.iter().map(char::to_string) {
+            let first = self
+                .words
+                .binary_search_by(|probe| probe.as_str().cmp(&start))
+                .unwrap_or_else(|index| index);
+            candidates.extend(
+                self.words[first..]
+                    .iter()
+                    .take_while(|word| word.starts_with(&start))
+                    .filter(|word| allowed(word))
+                    .map(String::as_str),
+            );
+        }
+        if candidates.is_empty() {
+            return None;
+        }
+        let index = rand::thread_rng().gen_range(0, candidates.len());
+        Some(candidates[index])
+    }
+
+    fn random(&self) -> Option<&str> {
+        if self.words.is_empty() {
+            return None;
+        }
+        let index = rand::thread_rng().gen_range(0, self.words.len());
+        Some(&self.words[index])
+    }
+}
+
+/// The 두음법칙 form of a syllable, such as 녀 → 여, 리 → 이 or 라 → 나.
+fn initial_sound(syllable: char) -> Option<char> {
+    let code = syllable as u32;
+    if code < HANGUL_FIRST || code > HANGUL_LAST {
+        return None;
+    }
+    let index = code - HANGUL_FIRST;
+    let (initial, medial, last) = (index / 588, index / 28 % 21, index % 28);
+    let y_vowel = Y_VOWELS.contains(&medial);
+    let initial = match initial {
+        NIEUN | RIEUL if y_vowel => IEUNG,
+        RIEUL => NIEUN,
+        _ => return None,
+    };
+    std::char::from_u32(HANGUL_FIRST + (initial * 21 + medial) * 28 + last)
+}
+
+pub(super) struct WordChain {
+    dictionary: Arc<Dictionary>,
+    initial_sound_rule: bool,
+    turn_time: Duration,
+    /// Seat order, with whether each player is still in.
+    players: Vec<(UserId, bool)>,
+    turn: usize,
+    word: String,
+    used: HashSet<String>,
+    /// Words each player got accepted this round.
+    scores: HashMap<UserId, u32>,
+    /// Only set while a round runs.
+    deadline: Option<Instant>,
+    events: Vec<GameEvent>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub(in super::super) struct Saved {
+    players: Vec<(UserId, bool)>,
+    turn: usize,
+    word: String,
+    used: HashSet<String>,
+    scores: HashMap<UserId, u32>,
+    left: Option<u64>,
+}
+
+impl WordChain {
+    pub(super) fn new(
+        dictionary: Arc<Dictionary>,
+        initial_sound_rule: bool,
+        turn_seconds: u64,
+    ) -> Self {
+        Self {
+            dictionary,
+            initial_sound_rule,
+            turn_time: Duration::from_secs(turn_seconds),
+            players: vec![],
+            turn: 0,
+            word: String::new(),
+            used: HashSet::new(),
+            scores: HashMap::new(),
+            deadline: None,
+            events: vec![],
+        }
+    }
+
+    fn starts(&self) -> Vec<char> {
+        let last = match self.word.chars().last() {
+            Some(last) => last,
+            None => return vec![],
+        };
+        let mut starts = vec![last];
+        if self.initial_sound_rule {
+            starts.extend(initial_sound(last));
+        }
+        starts
+    }
+
+    fn alive(&self) -> usize {
+        self.players.iter().filter(|&&(_, alive)| alive).count()
+    }
+
+    fn current(&self) -> Option<UserId> {
+        self.deadline?;
+        self.players.get(self.turn).map(|&(id, _)| id)
+    }
+
+    /// Hands the turn to the next player still in, or ends the round once
+    /// only one is left.
+    fn next_turn(&mut self, now: Instant) {
+        let count = self.players.len();
+        for step in 1..=count {
+            let turn = (self.turn + step) % count;
+            if self.players[turn].1 {
+                self.turn = turn;
+                break;
+            }
+        }
+        self.deadline = if self.alive() > 1 {
+            Some(now + self.turn_time)
+        } else {
+            None
+        };
+    }
+
+    fn eliminate(&mut self, index: usize, now: Instant) {
+        self.players[index].1 = false;
+        self.events.push(GameEvent::Eliminated {
+            player: self.players[index].0,
+        });
+        if index == self.turn {
+            self.next_turn(now);
+        } else if self.alive() <= 1 {
+            self.deadline = None;
+        }
+    }
+
+    fn check(&self, word: &str) -> Option<WordRejection> {
+        let starts = self.starts();
+        if word.chars().count() < MIN_WORD_LEN {
+            Some(WordRejection::TooShort)
+        } else if !word
+            .chars()
+            .next()
+            .map_or(false, |first| starts.contains(&first))
+        {
+            Some(WordRejection::WrongStart)
+        } else if self.used.contains(word) {
+            Some(WordRejection::AlreadyUsed)
+        } else if !self.dictionary.contains(word) {
+            Some(WordRejection::NotInDictionary)
+        } else {
+            None
+        }
+    }
+}
+
+impl Game for WordChain {
+    fn setup(&mut self, players: &[UserId], now: Instant) -> Result<(), Alert> {
+        if players.len() < 2 {
+            return Err(Alert::NotEnoughPlayers);
+        }
+        let word = self
+            .dictionary
+            .random()
+            .ok_or(Alert::GameUnavailable)?
+            .to_string();
+        self.players = players.iter().map(|&id| (id, true)).collect();
+        self.turn = 0;
+        self.used = HashSet::new();
+        self.used.insert(word.clone());
+        self.word = word;
+        self.scores.clear();
+        self.events.clear();
+        self.deadline = Some(now + self.turn_time);
+        Ok(())
+    }
+
+    fn action(&mut self, player: UserId, action: GameAction, now: Instant) -> Result<(), Alert> {
+        let word = match action {
+            GameAction::Word { word } => word.trim().to_string(),
+            _ => return Err(Alert::InvalidMove),
+        };
+        if self.current() != Some(player) {
+            return Err(Alert::InvalidMove);
+        }
+        match self.check(&word) {
+            Some(reason) => self.events.push(GameEvent::WordRejected {
+                player,
+                word,
+                reason,
+            }),
+            None => {
+                self.events.push(GameEvent::WordAccepted {
+                    player,
+                    word: word.clone(),
+                });
+                self.used.insert(word.clone());
+                self.word = word;
+                *self.scores.entry(player).or_insert(0) += 1;
+                self.next_turn(now);
+            }
+        }
+        Ok(())
+    }
+
+    fn tick(&mut self, now: Instant) {
+        match self.deadline {
+            Some(deadline) if deadline <= now => self.eliminate(self.turn, now),
+            _ => (),
+        }
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    fn leave(&mut self, player: UserId) {
+        let index = self
+            .players
+            .iter()
+            .position(|&(id, alive)| id == player && alive);
+        if let Some(index) = index {
+            self.eliminate(index, Instant::now());
+        }
+    }
+
+    fn view(&self, _: Option<UserId>) -> GameView {
+        GameView::WordChain {
+            players: self.players.clone(),
+            turn: self.current(),
+            word: self.word.clone(),
+            starts: self.starts(),
+            initial_sound_rule: self.initial_sound_rule,
+        }
+    }
+
+    fn save(&self, now: Instant) -> SavedGame {
+        SavedGame::WordChain(Saved {
+            players: self.players.clone(),
+            turn: self.turn,
+            word: self.word.clone(),
+            used: self.used.clone(),
+            scores: self.scores.clone(),
+            left: time_left(self.deadline, now),
+        })
+    }
+
+    fn restore(&mut self, saved: SavedGame, now: Instant) -> bool {
+        match saved {
+            SavedGame::WordChain(saved) => {
+                self.players = saved.players;
+                self.turn = saved.turn;
+                self.word = saved.word;
+                self.used = saved.used;
+                self.scores = saved.scores;
+                self.events.clear();
+                self.deadline = deadline_in(saved.left, now);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn outcome(&self) -> Option<GameOutcome> {
+        if self.players.is_empty() || self.deadline.is_some() {
+            return None;
+        }
+        Some(GameOutcome {
+            winners: self
+                .players
+                .iter()
+                .filter(|&&(_, alive)| alive)
+                .map(|&(id, _)| id)
+                .collect(),
+            scores: self
+                .scores
+                .iter()
+                .map(|(&id, &score)| (id, score))
+                .collect(),
+        })
+    }
+
+    fn take_events(&mut self) -> Vec<GameEvent> {
+        std::mem::replace(&mut self.events, vec![])
+    }
+}
+
+/// Plays a random word it hasn't seen yet, and runs out of time when there
+/// is none.
+pub(super) struct WordChainBot {
+    dictionary: Arc<Dictionary>,
+    seen: HashSet<String>,
+}
+
+impl WordChainBot {
+    pub(super) fn new(dictionary: Arc<Dictionary>) -> Self {
+        Self {
+            dictionary,
+            seen: HashSet::new(),
+        }
+    }
+}
+
+impl Strategy for WordChainBot {
+    fn act(&mut self, me: UserId, view: &GameView) -> Option<GameAction> {
+        let (turn, word, starts) = match view {
+            GameView::WordChain {
+                turn, word, starts, ..
+            } => (*turn, word, starts),
+            _ => return None,
+        };
+        self.seen.insert(word.clone());
+        if turn != Some(me) {
+            return None;
+        }
+        let seen = &self.seen;
+        let word = self
+            .dictionary
+            .pick(starts, |word| !seen.contains(word))?
+            .to_string();
+        self.seen.insert(word.clone());
+        Some(GameAction::Word { word })
+    }
+
+    /// Words may be played again in the next round.
+    fn reset(&mut self) {
+        self.seen.clear();
+    }
+}
@@ -0,0 +1,73 @@
+use super::Host;
+use actix::prelude::*;
+use bridge::api::LeaderboardEntry;
+use bridge::*;
+
+/// Most entries one leaderboard request may ask for.
+pub const MAX_LEADERBOARD: usize = 100;
+
+#[derive(Message)]
+#[rtype(result = "Vec<LeaderboardEntry>")]
+pub struct Leaderboard {
+    pub game: GameType,
+    pub limit: usize,
+}
+
+impl Host {
+    /// Guests have no rating.
+    fn rating(&self, id: UserId, game: GameType) -> Option<i32> {
+        self.accounts.get(id)?;
+        Some(self.ratings.get(game, id).rating.round() as i32)
+    }
+
+    /// The alert refusing `id` a seat in a room, if its range leaves them out.
+    pub(super) fn rating_refusal(&self, id: UserId, brief: &RoomBrief) -> Option<Alert> {
+        let range = brief.rating?;
+        match self.rating(id, brief.game.game_type()) {
+            Some(rating) if range.contains(rating) => None,
+            _ => Some(Alert::RatingOutOfRange { range }),
+        }
+    }
+
+    /// Whether `id` is an account that plays for a rating. Bots answer to
+    /// their owners, who could farm points off them.
+    fn rated(&self, id: UserId) -> bool {
+        self.accounts.get(id).map_or(false, |account| !account.bot)
+    }
+
+    /// Moves the ratings of the accounts that played a round.
+    pub(super) fn rate(&mut self, record: &MatchRecord) {
+        let players = record
+            .players
+            .iter()
+            .filter(|player| self.rated(player.id))
+            .map(|player| {
+                let won = record.winners.contains(&player.id);
+                (player.id, (won, player.score))
+            })
+            .collect::<Vec<_>>();
+        self.ratings.update(record.game.game_type(), &players);
+    }
+}
+
+impl Handler<Leaderboard> for Host {
+    type Result = MessageResult<Leaderboard>;
+
+    fn handle(&mut self, message: Leaderboard, _: &mut Context<Self>) -> Self::Result {
+        let limit = message.limit.min(MAX_LEADERBOARD);
+        let entries = self
+            .ratings
+            .top(message.game, limit, |id| self.rated(id))
+            .into_iter()
+            .filter_map(|(user, rating)| {
+                Some(LeaderboardEntry {
+                    user,
+                    name: self.accounts.get(user)?.name.clone(),
+                    rating: rating.rating.round() as i32,
+                    games: rating.games,
+                })
+            })
+            .collect();
+        MessageResult(entries)
+    }
+}
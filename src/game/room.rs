@@ -0,0 +1,759 @@
+use super::chatlog::ChatLog;
+use super::history::Finished;
+use super::play::{Game, SavedGame};
+use super::timer::Timer;
+use super::{unix_millis, unix_time, Chat, Host, Session};
+use bridge::api::ChatRecord;
+use actix::prelude::*;
+use bridge::server::Message;
+use bridge::*;
+use log::info;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+const COUNTDOWN: Duration = Duration::from_secs(3);
+/// Time to look at the results before the owner may start again.
+const ROUND_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Message)]
+pub(super) struct Enter {
+    pub(super) session: Session,
+    pub(super) spectator: bool,
+}
+
+#[derive(Message)]
+pub(super) struct Leave {
+    pub(super) id: UserId,
+}
+
+/// Replaces the room's copy of a session after the host changed it.
+#[derive(Message)]
+pub(super) struct Refresh {
+    pub(super) session: Session,
+}
+
+/// Gives the room to a member, as when its saved owner comes back after
+/// a restart.
+#[derive(Message)]
+pub(super) struct TakeOwnership {
+    pub(super) id: UserId,
+}
+
+/// Looks up a reported line in the room's chat.
+#[derive(Message)]
+#[rtype(result = "Option<Vec<ChatRecord>>")]
+pub(super) struct ChatContext {
+    pub(super) message_id: ChatId,
+    pub(super) viewer: UserId,
+}
+
+/// Asks for the room's state to write into a snapshot.
+#[derive(Message)]
+#[rtype(State)]
+pub(super) struct Save;
+
+/// What a room keeps across a restart besides its brief.
+#[derive(Serialize, Deserialize, Clone, MessageResponse)]
+pub(super) struct State {
+    pub(super) owner: UserId,
+    pub(super) members: Vec<UserId>,
+    pub(super) spectators: Vec<UserId>,
+    pub(super) chat_log: ChatLog,
+    /// The round being played, if one was.
+    #[serde(default)]
+    pub(super) round: Option<SavedRound>,
+    /// The tournament match the room was opened for, if any.
+    #[serde(default)]
+    pub(super) bracket: Option<(TournamentId, usize)>,
+}
+
+/// A round cut off by a restart, to be played on where it stopped.
+#[derive(Serialize, Deserialize, Clone)]
+pub(super) struct SavedRound {
+    game: SavedGame,
+    record: MatchRecord,
+    started: u64,
+    /// Frames hold messages, which can only be serialized, so they are
+    /// kept that way.
+    frames: serde_json::Value,
+}
+
+/// Picks up a round saved before a restart, once the room has reopened.
+#[derive(Message)]
+pub(super) struct ResumeRound {
+    pub(super) round: SavedRound,
+}
+
+#[derive(Message)]
+pub(super) struct Detail {
+    pub(super) pipe: Recipient<Message>,
+}
+
+#[derive(Message)]
+pub struct StartGame {
+    pub id: UserId,
+}
+
+#[derive(Message)]
+pub struct Play {
+    pub id: UserId,
+    pub action: GameAction,
+}
+
+#[derive(Message)]
+pub struct ChooseTeam {
+    pub id: UserId,
+    pub team: Option<Team>,
+}
+
+#[derive(Message)]
+pub struct ShuffleTeams {
+    pub id: UserId,
+}
+
+#[derive(Message)]
+pub struct ClearTeams {
+    pub id: UserId,
+}
+
+/// Dedicates a room to a tournament match. Its rounds are reported as
+/// that match's, and its members may no longer pick teams.
+#[derive(Message)]
+pub(super) struct HostMatch {
+    pub(super) tournament: TournamentId,
+    pub(super) index: usize,
+}
+
+/// Tells the host when a round starts or ends, for the room list.
+#[derive(Message)]
+pub(super) struct Playing {
+    pub(super) room: RoomId,
+    pub(super) playing: bool,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Phase {
+    Idle,
+    Countdown,
+    Playing,
+    Interval,
+}
+
+pub struct Room {
+    id: RoomId,
+    title: String,
+    members: Vec<Session>,
+    spectators: Vec<Session>,
+    owner: UserId,
+    capacity: usize,
+    separate_spectators: bool,
+    chat_log: ChatLog,
+    /// Only members have a team, and teams are left alone during a round.
+    teams: HashMap<UserId, Team>,
+    /// Set where a shared win could carry a player, in rated rooms and
+    /// tournament matches.
+    teams_locked: bool,
+    /// The tournament and bracket match the room was opened for.
+    bracket: Option<(TournamentId, usize)>,
+    kind: GameKind,
+    game: Box<dyn Game>,
+    phase: Phase,
+    timer: Timer,
+    round: Option<Round>,
+    host: Addr<Host>,
+}
+
+/// The round being played, recorded for its replay.
+struct Round {
+    /// Filled in as the round ends.
+    record: MatchRecord,
+    /// Unix milliseconds, which frames count from.
+    started: u64,
+    frames: Vec<ReplayFrame>,
+}
+
+impl Room {
+    pub(super) fn new(
+        id: RoomId,
+        brief: &RoomBrief,
+        owner: Session,
+        chat_log: ChatLog,
+        game: Box<dyn Game>,
+        host: Addr<Host>,
+    ) -> Self {
+        Self {
+            id,
+            title: brief.title.clone(),
+            capacity: brief.capacity,
+            separate_spectators: brief.separate_spectators,
+            owner: owner.id,
+            members: vec![owner],
+            spectators: vec![],
+            chat_log,
+            teams: HashMap::new(),
+            teams_locked: brief.rating.is_some(),
+            bracket: None,
+            kind: brief.game,
+            game,
+            phase: Phase::Idle,
+            timer: Timer::default(),
+            round: None,
+            host,
+        }
+    }
+
+    /// Sends to players and spectators alike.
+    fn broadcast(&self, message: impl Fn() -> Message) {
+        for session in self.members.iter().chain(self.spectators.iter()) {
+            let _ = session.pipe.do_send(message());
+        }
+    }
+
+    /// Keeps a message everyone in the room saw for the replay.
+    fn record(&mut self, message: Message) {
+        if let Some(round) = self.round.as_mut() {
+            round.frames.push(ReplayFrame {
+                at: unix_millis().saturating_sub(round.started),
+                message,
+            });
+        }
+    }
+
+    fn send_views(&mut self) {
+        self.record(Message::GameUpdate {
+            view: self.game.view(None),
+        });
+        for session in self.members.iter() {
+            let _ = session.pipe.do_send(Message::GameUpdate {
+                view: self.game.view(Some(session.id)),
+            });
+        }
+        for session in self.spectators.iter() {
+            let _ = session.pipe.do_send(Message::GameUpdate {
+                view: self.game.view(None),
+            });
+        }
+    }
+
+    /// Whether the room counts as busy in the room list.
+    fn playing(&self) -> bool {
+        self.phase == Phase::Countdown || self.phase == Phase::Playing
+    }
+
+    fn set_phase(&mut self, phase: Phase) {
+        let playing = self.playing();
+        self.phase = phase;
+        if playing != self.playing() {
+            self.host.do_send(Playing {
+                room: self.id,
+                playing: self.playing(),
+            });
+        }
+        self.broadcast(|| Message::RoomUpdate { room: (&*self).into() });
+    }
+
+    /// Runs `then` at `at` in place of any earlier deadline, and shows
+    /// everyone the new one.
+    fn set_timer<F>(&mut self, ctx: &mut Context<Self>, kind: TimerKind, at: Instant, then: F)
+    where
+        F: FnOnce(&mut Room, &mut Context<Room>) + 'static,
+    {
+        let deadline = self.timer.set(ctx, kind, at, |room: &mut Room, ctx| {
+            room.timer.fired();
+            then(room, ctx);
+        });
+        self.broadcast(|| Message::Timer {
+            deadline: Some(deadline),
+            now: unix_millis(),
+        });
+        self.record(Message::Timer {
+            deadline: Some(deadline),
+            now: unix_millis(),
+        });
+    }
+
+    fn clear_timer(&mut self, ctx: &mut Context<Self>) {
+        if self.timer.cancel(ctx) {
+            self.broadcast(|| Message::Timer {
+                deadline: None,
+                now: unix_millis(),
+            });
+            self.record(Message::Timer {
+                deadline: None,
+                now: unix_millis(),
+            });
+        }
+    }
+
+    /// Deals the players in once the countdown is over.
+    fn begin(&mut self, ctx: &mut Context<Self>) {
+        let players = self
+            .members
+            .iter()
+            .map(|session| session.id)
+            .collect::<Vec<_>>();
+        if let Err(alert) = self.game.setup(&players, Instant::now()) {
+            if let Some(owner) = self.members.iter().find(|session| session.id == self.owner) {
+                let _ = owner.pipe.do_send(Message::Alert(alert));
+            }
+            self.set_phase(Phase::Idle);
+            return;
+        }
+        info!("Room #{} started a round of {:?}", self.id, self.kind);
+        let record = MatchRecord {
+            room: self.id,
+            game: self.kind,
+            players: self
+                .members
+                .iter()
+                .map(|session| MatchPlayer {
+                    id: session.id,
+                    name: session.name.clone(),
+                    score: 0,
+                    team: self.teams.get(&session.id).cloned(),
+                })
+                .collect(),
+            time: unix_time(),
+            ..MatchRecord::default()
+        };
+        self.round = Some(Round {
+            record,
+            started: unix_millis(),
+            frames: vec![],
+        });
+        self.set_phase(Phase::Playing);
+        self.advance(ctx);
+    }
+
+    /// Catches up after the game changed: ends the round, or sleeps until
+    /// the game's next deadline.
+    fn advance(&mut self, ctx: &mut Context<Self>) {
+        for event in self.game.take_events() {
+            self.broadcast(|| Message::GameEvent {
+                event: event.clone(),
+            });
+            self.record(Message::GameEvent { event });
+        }
+        self.send_views();
+        if let Some(outcome) = self.game.outcome() {
+            if let Some(Round {
+                mut record, frames, ..
+            }) = self.round.take()
+            {
+                for player in record.players.iter_mut() {
+                    player.score = outcome
+                        .scores
+                        .iter()
+                        .find(|&&(id, _)| id == player.id)
+                        .map_or(0, |&(_, score)| score);
+                }
+                // Teammates share in a win, as long as there was a team to
+                // beat.
+                let teams = record
+                    .players
+                    .iter()
+                    .filter_map(|player| player.team)
+                    .collect::<Vec<_>>();
+                let winning = record
+                    .players
+                    .iter()
+                    .filter(|player| outcome.winners.contains(&player.id))
+                    .filter_map(|player| player.team)
+                    .collect::<Vec<_>>();
+                record.winners = outcome.winners;
+                if teams.iter().any(|&team| teams[0] != team) {
+                    record.teammates = record
+                        .players
+                        .iter()
+                        .filter(|player| !record.winners.contains(&player.id))
+                        .filter(|player| player.team.map_or(false, |team| winning.contains(&team)))
+                        .map(|player| player.id)
+                        .collect();
+                }
+                record.duration = unix_time().saturating_sub(record.time);
+                self.host
+                    .send(Finished {
+                        record,
+                        frames,
+                        bracket: self.bracket,
+                    })
+                    .into_actor(self)
+                    .then(|result, room, _| {
+                        if let Ok(result) = result {
+                            room.broadcast(|| Message::GameResult {
+                                result: result.clone(),
+                            });
+                        }
+                        fut::ok(())
+                    })
+                    .spawn(ctx);
+            }
+            self.set_phase(Phase::Interval);
+            let at = Instant::now() + ROUND_INTERVAL;
+            self.set_timer(ctx, TimerKind::Interval, at, |room, _| {
+                room.set_phase(Phase::Idle);
+            });
+            return;
+        }
+        match self.game.deadline() {
+            // A rejected move leaves the clock running as it was.
+            Some(deadline) if self.timer.at() == Some(deadline) => (),
+            Some(deadline) => self.set_timer(ctx, TimerKind::Turn, deadline, |room, ctx| {
+                room.game.tick(Instant::now());
+                room.advance(ctx);
+            }),
+            None => self.clear_timer(ctx),
+        }
+    }
+}
+
+impl Actor for Room {
+    type Context = Context<Self>;
+
+    fn stopped(&mut self, _: &mut Self::Context) {
+        info!("Room #{} closed", self.id);
+    }
+}
+
+impl Handler<Enter> for Room {
+    type Result = ();
+
+    fn handle(&mut self, message: Enter, _: &mut Context<Self>) {
+        let session = message.session;
+        let id = session.id;
+        if message.spectator {
+            self.spectators.push(session.clone());
+        } else {
+            self.members.push(session.clone());
+        }
+        let _ = session.pipe.do_send(Message::ReadyJoin {
+            room: (&*self).into(),
+        });
+        if message.spectator {
+            self.broadcast(|| Message::Alert(Alert::Spectate { user: id }));
+        } else {
+            self.broadcast(|| Message::Alert(Alert::Join { user: id }));
+        }
+        self.broadcast(|| Message::RoomUpdate { room: (&*self).into() });
+        if self.phase == Phase::Playing {
+            let _ = session.pipe.do_send(Message::GameUpdate {
+                view: self.game.view(None),
+            });
+        }
+        if let Some(deadline) = self.timer.deadline() {
+            let _ = session.pipe.do_send(Message::Timer {
+                deadline: Some(deadline),
+                now: unix_millis(),
+            });
+        }
+    }
+}
+
+impl Handler<Leave> for Room {
+    type Result = ();
+
+    fn handle(&mut self, message: Leave, ctx: &mut Context<Self>) {
+        let id = message.id;
+        let player = self.members.iter().any(|member| member.id == id);
+        self.members.retain(|member| member.id != id);
+        self.spectators.retain(|spectator| spectator.id != id);
+        self.teams.remove(&id);
+        // Spectators alone do not keep a room open.
+        if self.members.len() < 1 {
+            ctx.stop();
+            return;
+        } else if self.owner == id {
+            let owner = self
+                .members
+                .iter()
+                .find(|member| !member.bot)
+                .unwrap_or(&self.members[0]);
+            self.owner = owner.id;
+        }
+        self.broadcast(|| Message::Alert(Alert::Quit { user: id }));
+        self.broadcast(|| Message::RoomUpdate { room: (&*self).into() });
+        if self.phase == Phase::Playing && player {
+            self.game.leave(id);
+            self.advance(ctx);
+        }
+    }
+}
+
+impl Handler<StartGame> for Room {
+    type Result = ();
+
+    fn handle(&mut self, message: StartGame, ctx: &mut Context<Self>) {
+        if message.id != self.owner || self.phase != Phase::Idle {
+            return;
+        }
+        self.set_phase(Phase::Countdown);
+        let at = Instant::now() + COUNTDOWN;
+        self.set_timer(ctx, TimerKind::Countdown, at, |room, ctx| room.begin(ctx));
+    }
+}
+
+impl Handler<Play> for Room {
+    type Result = ();
+
+    fn handle(&mut self, message: Play, ctx: &mut Context<Self>) {
+        if self.phase != Phase::Playing {
+            return;
+        }
+        let player = match self.members.iter().find(|session| session.id == message.id) {
+            Some(player) => player,
+            None => return,
+        };
+        match self.game.action(message.id, message.action, Instant::now()) {
+            Ok(()) => self.advance(ctx),
+            Err(alert) => {
+                let _ = player.pipe.do_send(Message::Alert(alert));
+            }
+        }
+    }
+}
+
+impl Handler<ChooseTeam> for Room {
+    type Result = ();
+
+    fn handle(&mut self, message: ChooseTeam, _: &mut Context<Self>) {
+        if self.teams_locked
+            || self.playing()
+            || !self.members.iter().any(|member| member.id == message.id)
+        {
+            return;
+        }
+        match message.team {
+            Some(team) => self.teams.insert(message.id, team),
+            None => self.teams.remove(&message.id),
+        };
+        self.broadcast(|| Message::RoomUpdate { room: (&*self).into() });
+    }
+}
+
+impl Handler<ShuffleTeams> for Room {
+    type Result = ();
+
+    /// Splits every member into two teams as even as they can be.
+    fn handle(&mut self, message: ShuffleTeams, _: &mut Context<Self>) {
+        if self.teams_locked || message.id != self.owner || self.playing() {
+            return;
+        }
+        let mut members = self
+            .members
+            .iter()
+            .map(|session| session.id)
+            .collect::<Vec<_>>();
+        members.shuffle(&mut rand::thread_rng());
+        self.teams = members
+            .into_iter()
+            .zip([Team::Red, Team::Blue].iter().cloned().cycle())
+            .collect();
+        info!("Room #{} shuffled its teams", self.id);
+        self.broadcast(|| Message::RoomUpdate { room: (&*self).into() });
+    }
+}
+
+impl Handler<ClearTeams> for Room {
+    type Result = ();
+
+    fn handle(&mut self, message: ClearTeams, _: &mut Context<Self>) {
+        if message.id != self.owner || self.playing() {
+            return;
+        }
+        self.teams.clear();
+        self.broadcast(|| Message::RoomUpdate { room: (&*self).into() });
+    }
+}
+
+impl Handler<HostMatch> for Room {
+    type Result = ();
+
+    fn handle(&mut self, message: HostMatch, _: &mut Context<Self>) {
+        self.bracket = Some((message.tournament, message.index));
+        self.teams_locked = true;
+        self.teams.clear();
+        self.broadcast(|| Message::RoomUpdate { room: (&*self).into() });
+    }
+}
+
+impl Handler<Detail> for Room {
+    type Result = ();
+
+    fn handle(&mut self, message: Detail, _: &mut Context<Self>) {
+        let _ = message.pipe.do_send(Message::RoomDetail {
+            room: (&*self).into(),
+        });
+    }
+}
+
+impl Handler<Save> for Room {
+    type Result = State;
+
+    fn handle(&mut self, _: Save, _: &mut Context<Self>) -> Self::Result {
+        let round = match (self.phase, &self.round) {
+            (Phase::Playing, Some(round)) => Some(SavedRound {
+                game: self.game.save(Instant::now()),
+                record: round.record.clone(),
+                started: round.started,
+                frames: serde_json::to_value(&round.frames).unwrap_or_default(),
+            }),
+            _ => None,
+        };
+        State {
+            owner: self.owner,
+            members: self.members.iter().map(|session| session.id).collect(),
+            spectators: self.spectators.iter().map(|session| session.id).collect(),
+            chat_log: self.chat_log.clone(),
+            round,
+            bracket: self.bracket,
+        }
+    }
+}
+
+impl Handler<ResumeRound> for Room {
+    type Result = ();
+
+    fn handle(&mut self, message: ResumeRound, ctx: &mut Context<Self>) {
+        let SavedRound {
+            game,
+            record,
+            started,
+            frames,
+        } = message.round;
+        if self.phase != Phase::Idle || !self.game.restore(game, Instant::now()) {
+            return;
+        }
+        info!("Room #{} resumed its round", self.id);
+        self.round = Some(Round {
+            record,
+            started,
+            frames: serde_json::from_value(frames).unwrap_or_default(),
+        });
+        self.set_phase(Phase::Playing);
+        self.advance(ctx);
+    }
+}
+
+impl Handler<TakeOwnership> for Room {
+    type Result = ();
+
+    fn handle(&mut self, message: TakeOwnership, _: &mut Context<Self>) {
+        if self.members.iter().any(|member| member.id == message.id) {
+            self.owner = message.id;
+            self.broadcast(|| Message::RoomUpdate { room: (&*self).into() });
+        }
+    }
+}
+
+impl Handler<ChatContext> for Room {
+    type Result = Option<Vec<ChatRecord>>;
+
+    fn handle(&mut self, message: ChatContext, _: &mut Context<Self>) -> Self::Result {
+        self.chat_log.context(message.message_id, message.viewer)
+    }
+}
+
+impl Handler<Refresh> for Room {
+    type Result = ();
+
+    fn handle(&mut self, message: Refresh, _: &mut Context<Self>) {
+        let id = message.session.id;
+        let session = self
+            .members
+            .iter_mut()
+            .chain(self.spectators.iter_mut())
+            .find(|session| session.id == id);
+        if let Some(session) = session {
+            *session = message.session;
+        }
+    }
+}
+
+impl Handler<Chat> for Room {
+    type Result = ();
+
+    fn handle(&mut self, message: Chat, _: &mut Context<Self>) {
+        let text = message.text.trim().to_string();
+        if text.len() < 1 {
+            return;
+        }
+        let from = message.id;
+        let spectating = self.spectators.iter().any(|spectator| spectator.id == from);
+        let sender = match self
+            .members
+            .iter()
+            .chain(self.spectators.iter())
+            .find(|session| session.id == from)
+        {
+            Some(sender) => sender,
+            // A session may still be routed here right after it quit.
+            None => return,
+        };
+        if let Some(mute) = &sender.muted {
+            if mute.until > unix_time() {
+                let _ = sender.pipe.do_send(Message::Alert(Alert::Sanctioned {
+                    kind: mute.kind,
+                    reason: mute.reason.clone(),
+                    until: mute.until,
+                }));
+                return;
+            }
+        }
+        // Spectators have no team to talk to.
+        let team = match self.teams.get(&from) {
+            Some(&team) if message.team => Some(team),
+            _ if message.team => return,
+            _ => None,
+        };
+        let separated = spectating && self.separate_spectators;
+        let scope = if team.is_some() {
+            ChatScope::Team
+        } else if spectating {
+            ChatScope::Spectator
+        } else {
+            ChatScope::Public
+        };
+        let id = self
+            .chat_log
+            .push(from, None, Some(self.id), &text, scope);
+        let teams = &self.teams;
+        let listeners = self
+            .members
+            .iter()
+            .filter(|_| !separated)
+            .chain(self.spectators.iter().filter(|_| team.is_none()))
+            .filter(|session| team.is_none() || teams.get(&session.id) == team.as_ref())
+            .filter(|session| !session.blocked.contains(&from));
+        for session in listeners {
+            let _ = session.pipe.do_send(Message::Chat {
+                id,
+                from,
+                text: text.clone(),
+                scope,
+            });
+        }
+    }
+}
+
+impl From<&Room> for bridge::Room {
+    fn from(room: &Room) -> Self {
+        Self {
+            id: room.id,
+            title: room.title.clone(),
+            owner: room.owner,
+            members: room.members.iter().map(|session| session.id).collect(),
+            spectators: room.spectators.iter().map(|session| session.id).collect(),
+            capacity: room.capacity,
+            separate_spectators: room.separate_spectators,
+            game: room.kind,
+            playing: room.playing(),
+            teams: room
+                .members
+                .iter()
+                .filter_map(|session| room.teams.get(&session.id).map(|&team| (session.id, team)))
+                .collect(),
+        }
+    }
+}
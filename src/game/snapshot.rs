@@ -0,0 +1,242 @@
+use super::chatlog::ChatLog;
+use super::room::{HostMatch, ResumeRound, Save, State, TakeOwnership};
+use super::tournament::TournamentEntry;
+use super::{Host, Spectate};
+use crate::storage;
+use actix::prelude::*;
+use bridge::*;
+use futures::future::{self, Future};
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::time::Duration;
+
+const FILE: &str = "snapshot.json";
+pub(super) const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(60);
+/// How long restored sessions and rooms wait for their users to come back.
+pub(super) const RESUME_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Everything in the host worth carrying across a restart.
+#[derive(Serialize, Deserialize, Default)]
+pub struct Snapshot {
+    sessions: Vec<Resume>,
+    rooms: Vec<RestoredRoom>,
+    chat_log: ChatLog,
+    #[serde(default)]
+    tournaments: Vec<TournamentEntry>,
+}
+
+/// A session waiting for its user to reconnect.
+#[derive(Serialize, Deserialize, Clone)]
+pub(super) struct Resume {
+    pub(super) id: UserId,
+    pub(super) name: String,
+    pub(super) token: String,
+    pub(super) room: Option<RoomId>,
+    pub(super) spectating: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub(super) struct RestoredRoom {
+    brief: RoomBrief,
+    password: Option<String>,
+    criteria: MatchCriteria,
+    state: State,
+}
+
+impl Snapshot {
+    pub fn load() -> io::Result<Self> {
+        storage::load(FILE)
+    }
+}
+
+impl Host {
+    /// Takes in a snapshot written by an earlier run.
+    pub fn restore(&mut self, snapshot: Snapshot) {
+        snapshot.chat_log.reserve_ids();
+        for room in snapshot.rooms.iter() {
+            room.state.chat_log.reserve_ids();
+        }
+        info!(
+            "Restoring {} sessions, {} rooms and {} tournaments",
+            snapshot.sessions.len(),
+            snapshot.rooms.len(),
+            snapshot.tournaments.len()
+        );
+        self.chat_log = snapshot.chat_log;
+        self.restore_tournaments(snapshot.tournaments);
+        self.resumes = snapshot
+            .sessions
+            .into_iter()
+            .map(|resume| (resume.token.clone(), resume))
+            .collect();
+        self.restored = snapshot
+            .rooms
+            .into_iter()
+            .map(|room| (room.brief.id, room))
+            .collect();
+    }
+
+    pub(super) fn forget_restored(&mut self) {
+        if self.resumes.len() + self.restored.len() > 0 {
+            info!(
+                "{} sessions and {} rooms were never resumed",
+                self.resumes.len(),
+                self.restored.len()
+            );
+        }
+        self.resumes.clear();
+        self.restored.clear();
+        for room in self.rooms.values_mut() {
+            room.restored_owner = None;
+            room.awaited.clear();
+        }
+    }
+
+    /// Finds a restored session by account, or else by resume token.
+    pub(super) fn take_resume(
+        &mut self,
+        account: Option<UserId>,
+        token: Option<&str>,
+    ) -> Option<Resume> {
+        let token = match account {
+            Some(id) => self
+                .resumes
+                .values()
+                .find(|resume| resume.id == id)
+                .map(|resume| resume.token.clone())?,
+            None => token?.to_string(),
+        };
+        self.resumes.remove(&token)
+    }
+
+    /// Puts a resumed session back into its room. The first player back
+    /// reopens a restored room and holds it until the saved owner returns,
+    /// and a round cut off by the restart goes on from where it stopped.
+    pub(super) fn reclaim(&mut self, resume: &Resume, ctx: &mut Context<Self>) {
+        let room_id = match resume.room {
+            Some(room_id) => room_id,
+            None => return,
+        };
+        if self.rooms.contains_key(&room_id) {
+            if resume.spectating {
+                self.handle(
+                    Spectate {
+                        id: resume.id,
+                        room: room_id,
+                    },
+                    ctx,
+                );
+            } else {
+                self.enter(resume.id, room_id);
+                self.return_owner(resume.id, room_id);
+            }
+            return;
+        }
+        // Spectators alone do not bring a room back.
+        if resume.spectating {
+            return;
+        }
+        if let Some(room) = self.restored.remove(&room_id) {
+            let RestoredRoom {
+                brief,
+                password,
+                criteria,
+                state,
+            } = room;
+            self.open_room(resume.id, brief, password, criteria, state.chat_log, ctx);
+            if let Some(room) = self.rooms.get_mut(&room_id) {
+                if state.owner != resume.id {
+                    room.restored_owner = Some(state.owner);
+                }
+                if let Some((tournament, index)) = state.bracket {
+                    room.bracket = state.bracket;
+                    room.addr.do_send(HostMatch { tournament, index });
+                }
+                if let Some(round) = state.round {
+                    room.awaited = state.members;
+                    room.awaited.retain(|&player| player != resume.id);
+                    room.addr.do_send(ResumeRound { round });
+                }
+            }
+        }
+    }
+
+    /// Hands a reopened room back to its saved owner once they are seated.
+    fn return_owner(&mut self, id: UserId, room_id: RoomId) {
+        let room = match self.rooms.get_mut(&room_id) {
+            Some(room) => room,
+            None => return,
+        };
+        if room.restored_owner != Some(id) || !room.members.contains(&id) {
+            return;
+        }
+        room.restored_owner = None;
+        room.brief.owner = id;
+        room.addr.do_send(TakeOwnership { id });
+        self.broadcast_room(room_id);
+    }
+
+    /// Collects every room's state, writes the snapshot and then runs `then`.
+    pub(super) fn save_snapshot<F>(&mut self, ctx: &mut Context<Self>, then: F)
+    where
+        F: FnOnce(&mut Host, &mut Context<Host>) + 'static,
+    {
+        let states = self
+            .rooms
+            .iter()
+            .map(|(&id, room)| {
+                room.addr
+                    .send(Save)
+                    .then(move |state| Ok::<_, ()>((id, state.ok())))
+            })
+            .collect::<Vec<_>>();
+        future::join_all(states)
+            .into_actor(self)
+            .then(move |states, host, ctx| {
+                if let Ok(states) = states {
+                    host.write_snapshot(states);
+                }
+                then(host, ctx);
+                fut::ok(())
+            })
+            .spawn(ctx);
+    }
+
+    /// Builds the snapshot and hands it to the writer.
+    fn write_snapshot(&self, states: Vec<(RoomId, Option<State>)>) {
+        let live = states.into_iter().filter_map(|(id, state)| {
+            let room = self.rooms.get(&id)?;
+            let mut state = state?;
+            // An owner still away keeps their claim through another restart.
+            state.owner = room.restored_owner.unwrap_or(state.owner);
+            Some(RestoredRoom {
+                brief: room.brief.clone(),
+                password: room.password.clone(),
+                criteria: room.criteria.clone(),
+                state,
+            })
+        });
+        let snapshot = Snapshot {
+            sessions: self
+                .sessions
+                .values()
+                // Bots end with the process.
+                .filter(|session| !session.bot)
+                .map(|session| Resume {
+                    id: session.id,
+                    name: session.name.clone(),
+                    token: session.resume.clone(),
+                    room: session.room,
+                    spectating: session.spectating,
+                })
+                .chain(self.resumes.values().cloned())
+                .collect(),
+            rooms: live.chain(self.restored.values().cloned()).collect(),
+            chat_log: self.chat_log.clone(),
+            tournaments: self.tournaments.values().cloned().collect(),
+        };
+        // Serializing it all is left to the writer as well.
+        self.writer.save(FILE, snapshot);
+    }
+}
@@ -0,0 +1,141 @@
+use super::*;
+use actix::prelude::*;
+use bridge::server::Message;
+use futures::Future;
+use std::sync::{Arc, Mutex};
+
+/// Stands in for a WebSocket connection and ignores what it is sent.
+struct Probe;
+
+impl Actor for Probe {
+    type Context = Context<Self>;
+}
+
+impl Handler<Message> for Probe {
+    type Result = ();
+
+    fn handle(&mut self, _: Message, _: &mut Context<Self>) {}
+}
+
+impl Handler<Control> for Probe {
+    type Result = ();
+
+    fn handle(&mut self, _: Control, _: &mut Context<Self>) {}
+}
+
+/// The connection the host keeps for a user, if it keeps one.
+#[derive(Message)]
+#[rtype(result = "Option<u64>")]
+struct Current(UserId);
+
+impl Handler<Current> for Host {
+    type Result = Option<u64>;
+
+    fn handle(&mut self, message: Current, _: &mut Context<Self>) -> Self::Result {
+        self.sessions
+            .get(&message.0)
+            .map(|session| session.connection)
+    }
+}
+
+fn start(accounts: Accounts) -> Addr<Host> {
+    Host::new(
+        accounts,
+        Reports::default(),
+        Sanctions::default(),
+        AuditLog::default(),
+        Matches::default(),
+        Ratings::default(),
+        Dictionary::default(),
+    )
+    .start()
+}
+
+fn connect(host: &Addr<Host>, token: &str) -> impl Future<Item = Welcome, Error = MailboxError> {
+    let probe = Probe.start();
+    host.send(Connect {
+        addr: probe.clone().recipient(),
+        control: probe.recipient(),
+        token: Some(token.to_string()),
+        resume: None,
+        ip: None,
+    })
+}
+
+/// Runs `steps` against a fresh host holding one account, and returns the
+/// newest connection along with the one the host kept.
+fn run<F, R>(steps: F) -> (u64, Option<u64>)
+where
+    F: FnOnce(Addr<Host>, String) -> R + 'static,
+    R: Future<Item = Welcome, Error = MailboxError> + 'static,
+{
+    let mut accounts = Accounts::default();
+    let token = accounts.add_for_test("tester");
+    let result = Arc::new(Mutex::new(None));
+    let outcome = result.clone();
+    System::run(move || {
+        let host = start(accounts);
+        let check = host.clone();
+        Arbiter::spawn(
+            steps(host, token)
+                .and_then(move |newest| {
+                    check
+                        .send(Current(newest.id))
+                        .map(move |kept| (newest.connection, kept))
+                })
+                .then(move |result| {
+                    *outcome.lock().unwrap() = result.ok();
+                    System::current().stop();
+                    Ok::<_, ()>(())
+                }),
+        );
+    })
+    .unwrap();
+    let result = result.lock().unwrap().take();
+    result.expect("the host never answered")
+}
+
+#[test]
+fn late_disconnect_leaves_new_login() {
+    let (newest, kept) = run(|host, token| {
+        let again = host.clone();
+        connect(&host, &token)
+            .and_then(move |old| connect(&again, &token).map(move |new| (old, new)))
+            .and_then(move |(old, new)| {
+                assert_eq!(old.id, new.id);
+                host.send(Disconnect {
+                    id: old.id,
+                    connection: old.connection,
+                })
+                .map(move |_| new)
+            })
+    });
+    assert_eq!(kept, Some(newest));
+}
+
+#[test]
+fn disconnect_before_login_leaves_new_login() {
+    let (newest, kept) = run(|host, token| {
+        let again = host.clone();
+        let late = host.clone();
+        connect(&host, &token)
+            .and_then(move |old| {
+                host.send(Disconnect {
+                    id: old.id,
+                    connection: old.connection,
+                })
+                .map(move |_| old)
+            })
+            .and_then(move |old| connect(&again, &token).map(move |new| (old, new)))
+            // The same close may be reported twice; the second must miss.
+            .and_then(move |(old, new)| {
+                assert_eq!(old.id, new.id);
+                late.send(Disconnect {
+                    id: old.id,
+                    connection: old.connection,
+                })
+                .map(move |_| new)
+            })
+    });
+    assert_eq!(kept, Some(newest));
+}
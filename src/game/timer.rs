@@ -0,0 +1,70 @@
+use super::unix_millis;
+use actix::prelude::*;
+use bridge::{Deadline, TimerKind};
+use std::time::{Duration, Instant};
+
+/// The one pending deadline of an actor, kept so that it can be cancelled,
+/// moved and shown to clients.
+#[derive(Default)]
+pub(super) struct Timer {
+    pending: Option<(SpawnHandle, Instant, Deadline)>,
+}
+
+impl Timer {
+    /// Replaces whatever was pending with `then`, run at `at`.
+    pub(super) fn set<A, F>(
+        &mut self,
+        ctx: &mut Context<A>,
+        kind: TimerKind,
+        at: Instant,
+        then: F,
+    ) -> Deadline
+    where
+        A: Actor<Context = Context<A>>,
+        F: FnOnce(&mut A, &mut Context<A>) + 'static,
+    {
+        self.cancel(ctx);
+        let now = Instant::now();
+        let delay = if at > now {
+            at - now
+        } else {
+            Duration::from_secs(0)
+        };
+        let start = unix_millis();
+        let deadline = Deadline {
+            kind,
+            start,
+            end: start + delay.as_millis() as u64,
+        };
+        let handle = ctx.run_later(delay, then);
+        self.pending = Some((handle, at, deadline));
+        deadline
+    }
+
+    /// Returns whether anything was pending.
+    pub(super) fn cancel<A>(&mut self, ctx: &mut Context<A>) -> bool
+    where
+        A: Actor<Context = Context<A>>,
+    {
+        match self.pending.take() {
+            Some((handle, _, _)) => {
+                ctx.cancel_future(handle);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Forgets a timer that just went off.
+    pub(super) fn fired(&mut self) {
+        self.pending = None;
+    }
+
+    pub(super) fn at(&self) -> Option<Instant> {
+        self.pending.map(|(_, at, _)| at)
+    }
+
+    pub(super) fn deadline(&self) -> Option<Deadline> {
+        self.pending.map(|(_, _, deadline)| deadline)
+    }
+}
@@ -0,0 +1,580 @@
+use super::room::{HostMatch, StartGame};
+use super::{unix_time, CreateRoom, Host, InviteToRoom, QuitRoom};
+use crate::bracket::{self, Bracket};
+use actix::prelude::*;
+use bridge::server::Message;
+use bridge::*;
+use log::info;
+use rand::distributions::Alphanumeric;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// How often ready matches are checked for a room and both players.
+pub(super) const TOURNAMENT_INTERVAL: Duration = Duration::from_secs(10);
+const MAX_TOURNAMENTS: usize = 20;
+/// Tournaments one account may have open before any of them finishes.
+const MAX_ORGANIZED: usize = 2;
+/// Seconds a tournament may stay in sign-up before it is dropped.
+const SIGN_UP_TIMEOUT: u64 = 24 * 60 * 60;
+/// How long both players may sit in a match room before it starts without
+/// its owner.
+const START_DELAY: Duration = Duration::from_secs(60);
+/// How long a ready match waits for its players before those missing
+/// forfeit.
+const NO_SHOW_TIMEOUT: Duration = Duration::from_secs(3 * 60);
+/// How long players may stay in the room of a match they just finished
+/// before they are moved on to their next one.
+const MOVE_ON_DELAY: Duration = Duration::from_secs(10);
+const MAX_ENTRANTS: usize = 32;
+/// Match rooms are locked so only the invited players get in.
+const PASSWORD_LEN: usize = 16;
+
+#[derive(Message)]
+pub struct CreateTournament {
+    pub id: UserId,
+    pub tournament: NewTournament,
+}
+
+#[derive(Message)]
+pub struct JoinTournament {
+    pub id: UserId,
+    pub tournament: TournamentId,
+}
+
+#[derive(Message)]
+pub struct LeaveTournament {
+    pub id: UserId,
+    pub tournament: TournamentId,
+}
+
+#[derive(Message)]
+pub struct StartTournament {
+    pub id: UserId,
+    pub tournament: TournamentId,
+}
+
+#[derive(Message)]
+pub struct CancelTournament {
+    pub id: UserId,
+    pub tournament: TournamentId,
+}
+
+/// A tournament with the feeds its bracket moves players along.
+#[derive(Serialize, Deserialize, Clone)]
+pub(super) struct TournamentEntry {
+    tournament: Tournament,
+    bracket: Bracket,
+    /// Unix time it was created at.
+    created: u64,
+    /// When each ready match was first seen waiting on its players.
+    #[serde(skip)]
+    waiting: HashMap<usize, Instant>,
+}
+
+impl Host {
+    /// Takes in the tournaments from a snapshot.
+    pub(super) fn restore_tournaments(&mut self, tournaments: Vec<TournamentEntry>) {
+        for entry in tournaments {
+            let id = entry.tournament.id;
+            self.next_tournament = self.next_tournament.max(id);
+            self.tournaments.insert(id, entry);
+        }
+    }
+
+    /// Drops tournaments nobody started in time.
+    pub(super) fn expire_tournaments(&mut self) {
+        let now = unix_time();
+        let expired = self
+            .tournaments
+            .values()
+            .filter(|entry| {
+                entry.tournament.state == TournamentState::SignUp
+                    && now.saturating_sub(entry.created) >= SIGN_UP_TIMEOUT
+            })
+            .map(|entry| entry.tournament.id)
+            .collect::<Vec<_>>();
+        for id in expired {
+            self.tournaments.remove(&id);
+            info!("Tournament #{} expired in sign-up", id);
+            for session in self.sessions.values() {
+                let _ = session.pipe.do_send(Message::TournamentClosed { id });
+            }
+        }
+    }
+
+    /// Sends every tournament to a user who just connected.
+    pub(super) fn send_tournaments(&self, id: UserId) {
+        if let Some(session) = self.sessions.get(&id) {
+            let _ = session.pipe.do_send(Message::Tournaments {
+                tournaments: self
+                    .tournaments
+                    .values()
+                    .map(|entry| entry.tournament.clone())
+                    .collect(),
+            });
+        }
+    }
+
+    fn broadcast_tournament(&self, id: TournamentId) {
+        if let Some(entry) = self.tournaments.get(&id) {
+            for session in self.sessions.values() {
+                let _ = session.pipe.do_send(Message::TournamentChanged {
+                    tournament: entry.tournament.clone(),
+                });
+            }
+        }
+    }
+
+    /// The tournament `id` may start or cancel.
+    fn organized(&mut self, id: UserId, tournament: TournamentId) -> Option<&mut TournamentEntry> {
+        let admin = self.accounts.get(id).map_or(false, |account| account.admin);
+        self.tournaments
+            .get_mut(&tournament)
+            .filter(|entry| entry.tournament.organizer == id || admin)
+    }
+
+    /// Settles the bracket after a change and finishes the tournament once
+    /// the last match is decided.
+    fn settle_tournament(&mut self, id: TournamentId, ctx: &mut Context<Self>) {
+        if let Some(entry) = self.tournaments.get_mut(&id) {
+            let tournament = &mut entry.tournament;
+            entry
+                .bracket
+                .settle(&mut tournament.matches, &tournament.forfeited);
+            if let Some(winner) = tournament.matches.last().and_then(|game| game.winner) {
+                tournament.state = TournamentState::Finished;
+                tournament.champion = match winner {
+                    Slot::Player(id) => Some(id),
+                    _ => None,
+                };
+                info!("Tournament #{} won by {:?}", id, tournament.champion);
+            }
+        }
+        self.broadcast_tournament(id);
+        self.open_matches(ctx);
+    }
+
+    /// Gives every ready match a room of its own with both its players,
+    /// opening one under whichever player is free and inviting the other.
+    /// Players still sitting in the room of a decided match are moved on.
+    /// A match left idle starts on its own, and players who never turn up
+    /// for it forfeit.
+    pub(super) fn open_matches(&mut self, ctx: &mut Context<Self>) {
+        let now = Instant::now();
+        let mut ready = vec![];
+        for (&id, entry) in self.tournaments.iter_mut() {
+            if entry.tournament.state != TournamentState::Running {
+                continue;
+            }
+            let matches = bracket::ready(&entry.tournament.matches);
+            entry
+                .waiting
+                .retain(|index, _| matches.iter().any(|&(other, _)| other == *index));
+            for (index, players) in matches {
+                let room = entry.tournament.matches[index].room;
+                let since = *entry.waiting.entry(index).or_insert(now);
+                ready.push((id, index, players, room, since));
+            }
+        }
+        let mut no_shows = vec![];
+        for (id, index, players, room, since) in ready {
+            // Room ids are reused, so only the room opened for this match
+            // will do.
+            let room = room.filter(|room| {
+                self.rooms
+                    .get(room)
+                    .map_or(false, |room| room.bracket == Some((id, index)))
+            });
+            if room.is_none() && now.duration_since(since) >= MOVE_ON_DELAY {
+                self.move_on(id, players, ctx);
+            }
+            let room_id = room.or_else(|| self.open_match(id, index, players, ctx));
+            let seated = room_id.map_or(vec![], |room_id| {
+                players
+                    .iter()
+                    .cloned()
+                    .filter(|player| self.rooms[&room_id].members.contains(player))
+                    .collect()
+            });
+            if now.duration_since(since) >= NO_SHOW_TIMEOUT && seated.len() < 2 {
+                let missing = players
+                    .iter()
+                    .cloned()
+                    .filter(|player| !seated.contains(player));
+                no_shows.push((id, missing.collect::<Vec<_>>()));
+                continue;
+            }
+            let room_id = match room_id {
+                Some(room_id) => room_id,
+                None => continue,
+            };
+            if let Some(entry) = self.tournaments.get_mut(&id) {
+                entry.tournament.matches[index].room = Some(room_id);
+            }
+            let owner = self.rooms[&room_id].brief.owner;
+            if self.rooms[&room_id].brief.playing {
+                // A round in progress keeps the match from timing out.
+                if let Some(entry) = self.tournaments.get_mut(&id) {
+                    entry.waiting.insert(index, now);
+                }
+                continue;
+            }
+            if seated.len() == 2 && now.duration_since(since) >= START_DELAY {
+                self.rooms[&room_id].addr.do_send(StartGame { id: owner });
+                continue;
+            }
+            for &player in players.iter() {
+                let invited = self.rooms[&room_id].members.contains(&player)
+                    || self.invites.contains_key(&(player, room_id));
+                let free = self
+                    .sessions
+                    .get(&player)
+                    .map_or(false, |session| session.room.is_none());
+                if !invited && free && players.contains(&owner) {
+                    self.handle(
+                        InviteToRoom {
+                            id: owner,
+                            user: player,
+                        },
+                        ctx,
+                    );
+                }
+            }
+        }
+        let mut settled = vec![];
+        for (id, missing) in no_shows {
+            if let Some(entry) = self.tournaments.get_mut(&id) {
+                for player in missing {
+                    if !entry.tournament.forfeited.contains(&player) {
+                        info!("User {} missed a match in tournament #{}", player, id);
+                        entry.tournament.forfeited.push(player);
+                    }
+                }
+                settled.push(id);
+            }
+        }
+        settled.dedup();
+        for id in settled {
+            self.settle_tournament(id, ctx);
+        }
+    }
+
+    /// Takes `players` out of rooms whose tournament match is over, so
+    /// they are free for their next one.
+    fn move_on(&mut self, id: TournamentId, players: [UserId; 2], ctx: &mut Context<Self>) {
+        let matches = match self.tournaments.get(&id) {
+            Some(entry) => &entry.tournament.matches,
+            None => return,
+        };
+        let done = players
+            .iter()
+            .cloned()
+            .filter(|player| {
+                let room = self.sessions.get(player).and_then(|session| session.room);
+                room.and_then(|room| self.rooms.get(&room))
+                    .filter(|room| !room.brief.playing)
+                    .and_then(|room| room.bracket)
+                    .map_or(false, |(tournament, index)| {
+                        tournament == id && matches[index].winner.is_some()
+                    })
+            })
+            .collect::<Vec<_>>();
+        for player in done {
+            self.handle(QuitRoom { id: player }, ctx);
+        }
+    }
+
+    fn open_match(
+        &mut self,
+        id: TournamentId,
+        index: usize,
+        players: [UserId; 2],
+        ctx: &mut Context<Self>,
+    ) -> Option<RoomId> {
+        let owner = *players.iter().find(|player| {
+            self.sessions
+                .get(player)
+                .map_or(false, |session| session.room.is_none())
+        })?;
+        let tournament = &self.tournaments.get(&id)?.tournament;
+        let title = format!("{} #{}", tournament.name, index + 1);
+        let game = tournament.game;
+        let password = (&mut self.rng)
+            .sample_iter(&Alphanumeric)
+            .take(PASSWORD_LEN)
+            .collect();
+        self.handle(
+            CreateRoom {
+                id: owner,
+                room: RoomBrief {
+                    id: 0,
+                    title,
+                    owner,
+                    members: 0,
+                    capacity: 2,
+                    playing: false,
+                    separate_spectators: false,
+                    locked: true,
+                    game,
+                    rating: None,
+                },
+                password: Some(password),
+            },
+            ctx,
+        );
+        let room_id = self.sessions.get(&owner)?.room?;
+        let room = self.rooms.get_mut(&room_id)?;
+        room.bracket = Some((id, index));
+        room.addr.do_send(HostMatch {
+            tournament: id,
+            index,
+        });
+        info!(
+            "Tournament #{} opened room #{} for match {}",
+            id, room_id, index
+        );
+        Some(room_id)
+    }
+
+    /// Advances the bracket whose match `record` was played for, as long
+    /// as it was the tournament's game between that match's two players.
+    pub(super) fn tournament_result(
+        &mut self,
+        record: &MatchRecord,
+        bracket: Option<(TournamentId, usize)>,
+        ctx: &mut Context<Self>,
+    ) {
+        let (id, index) = match bracket {
+            Some(bracket) => bracket,
+            None => return,
+        };
+        let entry = match self.tournaments.get_mut(&id) {
+            Some(entry) if entry.tournament.state == TournamentState::Running => entry,
+            _ => return,
+        };
+        if record.game.game_type() != entry.tournament.game.game_type() {
+            return;
+        }
+        let game = match entry.tournament.matches.get_mut(index) {
+            Some(game) if game.winner.is_none() => game,
+            _ => return,
+        };
+        let players = match game.slots {
+            [Slot::Player(a), Slot::Player(b)] => [a, b],
+            _ => return,
+        };
+        let played = record.players.len() == 2
+            && players
+                .iter()
+                .all(|id| record.players.iter().any(|player| player.id == *id));
+        // A tie leaves it for another round.
+        let winners = players
+            .iter()
+            .cloned()
+            .filter(|id| record.winners.contains(id))
+            .collect::<Vec<_>>();
+        if played && winners.len() == 1 {
+            game.winner = Some(Slot::Player(winners[0]));
+            self.settle_tournament(id, ctx);
+        }
+    }
+}
+
+impl Handler<CreateTournament> for Host {
+    type Result = ();
+
+    fn handle(&mut self, message: CreateTournament, _: &mut Context<Self>) {
+        if self.accounts.get(message.id).is_none() {
+            self.alert(message.id, Alert::AccountRequired);
+            return;
+        }
+        let name = message.tournament.name.trim().to_string();
+        if name.len() < 1 {
+            return;
+        }
+        let game = message.tournament.game.game_type();
+        if !super::play::available(&self.dictionary).contains(&game) {
+            self.alert(message.id, Alert::GameUnavailable);
+            return;
+        }
+        let organized = self
+            .tournaments
+            .values()
+            .filter(|entry| {
+                entry.tournament.organizer == message.id
+                    && entry.tournament.state != TournamentState::Finished
+            })
+            .count();
+        if organized >= MAX_ORGANIZED {
+            self.alert(message.id, Alert::TooManyTournaments);
+            return;
+        }
+        // Finished tournaments make way for new ones.
+        if self.tournaments.len() >= MAX_TOURNAMENTS {
+            let finished = self
+                .tournaments
+                .values()
+                .find(|entry| entry.tournament.state == TournamentState::Finished)
+                .map(|entry| entry.tournament.id);
+            match finished {
+                Some(id) => {
+                    self.tournaments.remove(&id);
+                    for session in self.sessions.values() {
+                        let _ = session.pipe.do_send(Message::TournamentClosed { id });
+                    }
+                }
+                None => {
+                    self.alert(message.id, Alert::TooManyTournaments);
+                    return;
+                }
+            }
+        }
+        let capacity = match message.tournament.capacity {
+            0 => MAX_ENTRANTS,
+            capacity => capacity.max(2).min(MAX_ENTRANTS),
+        };
+        self.next_tournament += 1;
+        let id = self.next_tournament;
+        info!("User {} created tournament #{}", message.id, id);
+        self.tournaments.insert(
+            id,
+            TournamentEntry {
+                tournament: Tournament {
+                    id,
+                    name,
+                    game: super::play::sanitize(message.tournament.game),
+                    elimination: message.tournament.elimination,
+                    organizer: message.id,
+                    capacity,
+                    entrants: vec![],
+                    forfeited: vec![],
+                    state: TournamentState::SignUp,
+                    matches: vec![],
+                    champion: None,
+                },
+                bracket: Bracket::default(),
+                created: unix_time(),
+                waiting: HashMap::new(),
+            },
+        );
+        self.broadcast_tournament(id);
+    }
+}
+
+impl Handler<JoinTournament> for Host {
+    type Result = ();
+
+    fn handle(&mut self, message: JoinTournament, _: &mut Context<Self>) {
+        let user = match self.accounts.get(message.id) {
+            Some(account) => UserBrief {
+                id: account.id,
+                name: account.name.clone(),
+                bot: account.bot,
+            },
+            None => {
+                self.alert(message.id, Alert::AccountRequired);
+                return;
+            }
+        };
+        let tournament = match self.tournaments.get_mut(&message.tournament) {
+            Some(entry) => &mut entry.tournament,
+            None => return,
+        };
+        if tournament.state != TournamentState::SignUp {
+            return;
+        }
+        if tournament
+            .entrants
+            .iter()
+            .any(|entrant| entrant.id == user.id)
+        {
+            return;
+        }
+        if tournament.entrants.len() >= tournament.capacity {
+            self.alert(message.id, Alert::TournamentFull);
+            return;
+        }
+        tournament.entrants.push(user);
+        self.broadcast_tournament(message.tournament);
+    }
+}
+
+impl Handler<LeaveTournament> for Host {
+    type Result = ();
+
+    fn handle(&mut self, message: LeaveTournament, ctx: &mut Context<Self>) {
+        let tournament = match self.tournaments.get_mut(&message.tournament) {
+            Some(entry) => &mut entry.tournament,
+            None => return,
+        };
+        if !tournament.entrants.iter().any(|user| user.id == message.id) {
+            return;
+        }
+        match tournament.state {
+            TournamentState::SignUp => {
+                tournament.entrants.retain(|user| user.id != message.id);
+                self.broadcast_tournament(message.tournament);
+            }
+            TournamentState::Running if !tournament.forfeited.contains(&message.id) => {
+                info!(
+                    "User {} forfeited tournament #{}",
+                    message.id, tournament.id
+                );
+                tournament.forfeited.push(message.id);
+                self.settle_tournament(message.tournament, ctx);
+            }
+            _ => (),
+        }
+    }
+}
+
+impl Handler<StartTournament> for Host {
+    type Result = ();
+
+    fn handle(&mut self, message: StartTournament, ctx: &mut Context<Self>) {
+        let mut rng = self.rng.clone();
+        let entry = match self.organized(message.id, message.tournament) {
+            Some(entry) => entry,
+            None => return,
+        };
+        if entry.tournament.state != TournamentState::SignUp {
+            return;
+        }
+        if entry.tournament.entrants.len() < 2 {
+            self.alert(message.id, Alert::NotEnoughPlayers);
+            return;
+        }
+        let mut seeds = entry
+            .tournament
+            .entrants
+            .iter()
+            .map(|user| user.id)
+            .collect::<Vec<_>>();
+        seeds.shuffle(&mut rng);
+        let (bracket, matches) = Bracket::new(&seeds, entry.tournament.elimination);
+        entry.bracket = bracket;
+        entry.tournament.matches = matches;
+        entry.tournament.state = TournamentState::Running;
+        info!("Tournament #{} started", message.tournament);
+        self.settle_tournament(message.tournament, ctx);
+    }
+}
+
+impl Handler<CancelTournament> for Host {
+    type Result = ();
+
+    fn handle(&mut self, message: CancelTournament, _: &mut Context<Self>) {
+        let id = message.tournament;
+        if self.organized(message.id, id).is_none() {
+            return;
+        }
+        self.tournaments.remove(&id);
+        info!("Tournament #{} cancelled by {}", id, message.id);
+        for session in self.sessions.values() {
+            let _ = session.pipe.do_send(Message::TournamentClosed { id });
+        }
+    }
+}
@@ -1,20 +1,47 @@
 use actix::*;
-use actix_web::{web, App, Error, HttpRequest, HttpResponse, HttpServer};
+use actix_web::{error, web, App, Error, HttpRequest, HttpResponse, HttpServer};
 use actix_web_actors::ws;
 
 use bridge::server::Message;
-use bridge::UserId;
+use bridge::{Alert, DisconnectReason, UserId};
+use futures::Future;
 use log::{info, warn};
+use serde::Deserialize;
 use std::time::{Duration, Instant};
+mod account;
+mod api;
+mod audit;
+mod bracket;
 mod game;
+mod matches;
+mod ratelimit;
+mod rating;
+mod replay;
+mod report;
+mod sanction;
+mod storage;
 
 const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
 const CLIENT_TIMEOUT: Duration = Duration::from_secs(10);
 
 struct WsSession {
     id: UserId,
+    connection: u64,
     hb: Instant,
     host: Addr<game::Host>,
+    room: Option<Addr<game::Room>>,
+    token: Option<String>,
+    resume: Option<String>,
+    ip: Option<String>,
+    limit: ratelimit::Bucket,
+    /// Already told the client it is sending too fast.
+    throttled: bool,
+}
+
+#[derive(Deserialize)]
+struct ConnectQuery {
+    token: Option<String>,
+    resume: Option<String>,
 }
 
 impl Actor for WsSession {
@@ -26,12 +53,22 @@ impl Actor for WsSession {
         let me = ctx.address();
         self.host
             .send(game::Connect {
-                addr: me.recipient(),
+                addr: me.clone().recipient(),
+                control: me.recipient(),
+                token: self.token.take(),
+                resume: self.resume.take(),
+                ip: self.ip.take(),
             })
             .into_actor(self)
             .then(|result, actor, ctx| {
                 match result {
-                    Ok(result) => actor.id = result.id,
+                    Ok(result) => {
+                        actor.id = result.id;
+                        actor.connection = result.connection;
+                        if result.bot {
+                            actor.limit = ratelimit::Bucket::new(&ratelimit::BOT);
+                        }
+                    }
                     _ => ctx.stop(),
                 }
                 fut::ok(())
@@ -40,7 +77,10 @@ impl Actor for WsSession {
     }
 
     fn stopping(&mut self, _: &mut Self::Context) -> Running {
-        self.host.do_send(game::Disconnect { id: self.id });
+        self.host.do_send(game::Disconnect {
+            id: self.id,
+            connection: self.connection,
+        });
         Running::Stop
     }
 }
@@ -53,6 +93,18 @@ impl Handler<Message> for WsSession {
     }
 }
 
+impl Handler<game::Control> for WsSession {
+    type Result = ();
+
+    fn handle(&mut self, control: game::Control, ctx: &mut Self::Context) {
+        match control {
+            game::Control::Enter(room) => self.room = Some(room),
+            game::Control::Leave => self.room = None,
+            game::Control::Close(reason) => close(ctx, reason),
+        }
+    }
+}
+
 impl StreamHandler<ws::Message, ws::ProtocolError> for WsSession {
     fn handle(&mut self, message: ws::Message, ctx: &mut Self::Context) {
         match message {
@@ -65,19 +117,34 @@ impl StreamHandler<ws::Message, ws::ProtocolError> for WsSession {
             }
             ws::Message::Binary(binary) => {
                 use bridge::client::Message;
+                if !self.limit.take(Instant::now()) {
+                    if !self.throttled {
+                        self.throttled = true;
+                        let alert = bridge::server::Message::Alert(Alert::RateLimited);
+                        ctx.binary(serde_json::to_vec(&alert).unwrap());
+                    }
+                    return;
+                }
+                self.throttled = false;
                 if let Ok(message) = serde_json::from_slice::<Message>(&binary) {
                     match message {
-                        Message::Chat { text, to } => {
-                            self.host.do_send(game::Chat {
+                        Message::Chat { text, to, team } => {
+                            let chat = game::Chat {
                                 id: self.id,
                                 text,
                                 to,
-                            });
+                                team,
+                            };
+                            match &self.room {
+                                Some(room) if chat.to.is_none() => room.do_send(chat),
+                                _ => self.host.do_send(chat),
+                            }
                         }
-                        Message::CreateRoom { room } => {
+                        Message::CreateRoom { room, password } => {
                             self.host.do_send(game::CreateRoom {
                                 id: self.id,
                                 room,
+                                password,
                             });
                         }
                         Message::GetRoomDetail { room } => {
@@ -86,24 +153,188 @@ impl StreamHandler<ws::Message, ws::ProtocolError> for WsSession {
                                 room,
                             });
                         }
-                        Message::JoinRoom { room } => {
+                        Message::QueryRooms { query } => {
+                            self.host.do_send(game::QueryRooms {
+                                id: self.id,
+                                query,
+                            });
+                        }
+                        Message::JoinRoom { room, password } => {
                             self.host.do_send(game::JoinRoom {
                                 id: self.id,
                                 room,
+                                password,
+                            });
+                        }
+                        Message::Spectate { room } => {
+                            self.host.do_send(game::Spectate {
+                                id: self.id,
+                                room,
+                            });
+                        }
+                        Message::QuickJoin { criteria } => {
+                            self.host.do_send(game::QuickJoin {
+                                id: self.id,
+                                criteria,
+                            });
+                        }
+                        Message::CancelQuickJoin => {
+                            self.host.do_send(game::CancelQuickJoin {
+                                id: self.id,
                             });
                         }
                         Message::QuitRoom => {
+                            self.room = None;
                             self.host.do_send(game::QuitRoom {
                                 id: self.id,
                             });
                         }
+                        Message::InviteToRoom { user } => {
+                            self.host.do_send(game::InviteToRoom {
+                                id: self.id,
+                                user,
+                            });
+                        }
+                        Message::AcceptInvite { room } => {
+                            self.host.do_send(game::AnswerInvite {
+                                id: self.id,
+                                room,
+                                accept: true,
+                            });
+                        }
+                        Message::DeclineInvite { room } => {
+                            self.host.do_send(game::AnswerInvite {
+                                id: self.id,
+                                room,
+                                accept: false,
+                            });
+                        }
+                        Message::RequestFriend { user } => {
+                            self.host.do_send(game::RequestFriend {
+                                id: self.id,
+                                user,
+                            });
+                        }
+                        Message::AcceptFriend { user } => {
+                            self.host.do_send(game::AcceptFriend {
+                                id: self.id,
+                                user,
+                            });
+                        }
+                        Message::RemoveFriend { user } => {
+                            self.host.do_send(game::RemoveFriend {
+                                id: self.id,
+                                user,
+                            });
+                        }
+                        Message::Block { user } => {
+                            self.host.do_send(game::Block {
+                                id: self.id,
+                                user,
+                                block: true,
+                            });
+                        }
+                        Message::Unblock { user } => {
+                            self.host.do_send(game::Block {
+                                id: self.id,
+                                user,
+                                block: false,
+                            });
+                        }
+                        Message::Report {
+                            user,
+                            reason,
+                            message_id,
+                        } => {
+                            self.host.do_send(game::Report {
+                                id: self.id,
+                                user,
+                                reason,
+                                message_id,
+                            });
+                        }
+                        Message::StartGame => {
+                            if let Some(room) = &self.room {
+                                room.do_send(game::StartGame { id: self.id });
+                            }
+                        }
+                        Message::ChooseTeam { team } => {
+                            if let Some(room) = &self.room {
+                                room.do_send(game::ChooseTeam { id: self.id, team });
+                            }
+                        }
+                        Message::ShuffleTeams => {
+                            if let Some(room) = &self.room {
+                                room.do_send(game::ShuffleTeams { id: self.id });
+                            }
+                        }
+                        Message::ClearTeams => {
+                            if let Some(room) = &self.room {
+                                room.do_send(game::ClearTeams { id: self.id });
+                            }
+                        }
+                        Message::GameAction { action } => {
+                            if let Some(room) = &self.room {
+                                room.do_send(game::Play {
+                                    id: self.id,
+                                    action,
+                                });
+                            }
+                        }
+                        Message::AddBot => {
+                            self.host.do_send(game::AddBot {
+                                id: self.id,
+                            });
+                        }
+                        Message::RemoveBot { bot } => {
+                            self.host.do_send(game::RemoveBot {
+                                id: self.id,
+                                bot,
+                            });
+                        }
+                        Message::CreateTournament { tournament } => {
+                            self.host.do_send(game::CreateTournament {
+                                id: self.id,
+                                tournament,
+                            });
+                        }
+                        Message::JoinTournament { tournament } => {
+                            self.host.do_send(game::JoinTournament {
+                                id: self.id,
+                                tournament,
+                            });
+                        }
+                        Message::LeaveTournament { tournament } => {
+                            self.host.do_send(game::LeaveTournament {
+                                id: self.id,
+                                tournament,
+                            });
+                        }
+                        Message::StartTournament { tournament } => {
+                            self.host.do_send(game::StartTournament {
+                                id: self.id,
+                                tournament,
+                            });
+                        }
+                        Message::CancelTournament { tournament } => {
+                            self.host.do_send(game::CancelTournament {
+                                id: self.id,
+                                tournament,
+                            });
+                        }
                     }
+                } else {
+                    warn!("Client {} sent an unreadable message", self.id);
+                    close(ctx, DisconnectReason::ProtocolError);
                 }
             }
             ws::Message::Close(_) => {
                 ctx.stop();
             }
-            ws::Message::Text(_) => warn!("Unexpected Text"),
+            ws::Message::Text(_) => {
+                warn!("Client {} sent Text", self.id);
+                close(ctx, DisconnectReason::ProtocolError);
+            }
             ws::Message::Nop => (),
         }
     }
@@ -115,9 +346,12 @@ impl WsSession {
             if Instant::now().duration_since(actor.hb) > CLIENT_TIMEOUT {
                 info!("Client {} heartbeat failed.", actor.id);
 
-                actor.host.do_send(game::Disconnect { id: actor.id });
+                actor.host.do_send(game::Disconnect {
+                    id: actor.id,
+                    connection: actor.connection,
+                });
 
-                ctx.stop();
+                close(ctx, DisconnectReason::Timeout);
                 return;
             }
             ctx.ping("");
@@ -125,32 +359,148 @@ impl WsSession {
     }
 }
 
+/// Tells the client why in a message it can read, repeats it in the close
+/// frame and stops the actor.
+fn close<A>(ctx: &mut ws::WebsocketContext<A>, reason: DisconnectReason)
+where
+    A: Actor<Context = ws::WebsocketContext<A>>,
+{
+    let message = Message::Disconnecting { reason };
+    ctx.binary(serde_json::to_vec(&message).unwrap());
+    ctx.close(Some(ws::CloseReason {
+        code: reason.code().into(),
+        description: Some(format!("{:?}", reason)),
+    }));
+    ctx.stop();
+}
+
+/// Tells a refused user why, then hangs up.
+struct Rejected {
+    alert: Option<Alert>,
+}
+
+impl Actor for Rejected {
+    type Context = ws::WebsocketContext<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        let alert = match self.alert.take() {
+            Some(alert) => alert,
+            None => return,
+        };
+        let reason = match alert {
+            Alert::Maintenance { .. } => DisconnectReason::Shutdown,
+            _ => DisconnectReason::Banned,
+        };
+        ctx.binary(serde_json::to_vec(&Message::Alert(alert)).unwrap());
+        close(ctx, reason);
+    }
+}
+
+impl StreamHandler<ws::Message, ws::ProtocolError> for Rejected {
+    fn handle(&mut self, _: ws::Message, _: &mut Self::Context) {}
+}
+
 fn game_route(
     req: HttpRequest,
     stream: web::Payload,
+    query: web::Query<ConnectQuery>,
     server: web::Data<Addr<game::Host>>,
-) -> Result<HttpResponse, Error> {
-    ws::start(
-        WsSession {
-            id: 0,
-            hb: Instant::now(),
-            host: server.get_ref().clone(),
-        },
-        &req,
-        stream,
-    )
+) -> impl Future<Item = HttpResponse, Error = Error> {
+    let host = server.get_ref().clone();
+    let ConnectQuery { token, resume } = query.into_inner();
+    // Clients other than browsers may send the token as a header instead.
+    let token = token.or_else(|| Some(api::bearer(&req)).filter(|token| !token.is_empty()));
+    let ip = req.peer_addr().map(|addr| addr.ip().to_string());
+    host.send(game::Admit {
+        token: token.clone(),
+        ip: ip.clone(),
+    })
+    .map_err(error::ErrorInternalServerError)
+    .and_then(move |admission| match admission {
+        Err(alert) => ws::start(Rejected { alert: Some(alert) }, &req, stream),
+        Ok(()) => ws::start(
+            WsSession {
+                id: 0,
+                connection: 0,
+                hb: Instant::now(),
+                host,
+                room: None,
+                token,
+                resume,
+                ip,
+                limit: ratelimit::Bucket::new(&ratelimit::PERSON),
+                throttled: false,
+            },
+            &req,
+            stream,
+        ),
+    })
 }
 
 fn main() -> std::io::Result<()> {
     simple_logger::init_with_level(log::Level::Info).unwrap();
     let sys = System::new("kiwitu");
-    let server = game::Host::default().start();
+    let accounts = account::Accounts::load().expect("Failed to load accounts");
+    let reports = report::Reports::load().expect("Failed to load reports");
+    let sanctions = sanction::Sanctions::load().expect("Failed to load sanctions");
+    let audit = audit::AuditLog::load().expect("Failed to load audit log");
+    let matches = matches::Matches::load().expect("Failed to load matches");
+    let ratings = rating::Ratings::load().expect("Failed to load ratings");
+    let dictionary = game::Dictionary::load().expect("Failed to load dictionary");
+    let mut host = game::Host::new(
+        accounts, reports, sanctions, audit, matches, ratings, dictionary,
+    );
+    // A broken snapshot only costs the players their rooms.
+    match game::Snapshot::load() {
+        Ok(snapshot) => host.restore(snapshot),
+        Err(e) => warn!("Failed to load snapshot: {}", e),
+    }
+    let server = host.start();
     HttpServer::new(move || {
         App::new()
             .data(server.clone())
-            .service(web::resource("/ws/").to(game_route))
+            .service(web::resource("/ws/").to_async(game_route))
+            .service(web::resource("/api/register").route(web::post().to_async(api::register)))
+            .service(web::resource("/api/login").route(web::post().to_async(api::login)))
+            .service(web::resource("/api/bots").route(web::post().to_async(api::create_bot)))
+            .service(
+                web::resource("/api/bots/{id}/token")
+                    .route(web::post().to_async(api::rotate_bot))
+                    .route(web::delete().to_async(api::revoke_bot)),
+            )
+            .service(web::resource("/api/leaderboard").route(web::get().to_async(api::leaderboard)))
+            .service(
+                web::resource("/api/matches/{id}/replay").route(web::get().to_async(api::replay)),
+            )
+            .service(
+                web::resource("/api/users/{id}/matches")
+                    .route(web::get().to_async(api::recent_matches)),
+            )
+            .service(web::resource("/api/admin/reports").route(web::get().to_async(api::list_reports)))
+            .service(
+                web::resource("/api/admin/reports/{id}/resolve")
+                    .route(web::post().to_async(api::resolve_report)),
+            )
+            .service(
+                web::resource("/api/admin/sanctions")
+                    .route(web::get().to_async(api::list_sanctions))
+                    .route(web::post().to_async(api::add_sanction)),
+            )
+            .service(
+                web::resource("/api/admin/sanctions/{id}")
+                    .route(web::delete().to_async(api::lift_sanction)),
+            )
+            .service(web::resource("/api/admin/kick/{id}").route(web::post().to_async(api::kick)))
+            .service(web::resource("/api/admin/audit").route(web::get().to_async(api::list_audit)))
+            .service(
+                web::resource("/api/admin/maintenance")
+                    .route(web::post().to_async(api::start_maintenance))
+                    .route(web::delete().to_async(api::end_maintenance)),
+            )
             .service(actix_files::Files::new("/", "static/").index_file("index.html"))
     })
+    // The host runs its own countdown before stopping the system.
+    .disable_signals()
     .bind("127.0.0.1:8080")?
     .start();
 
@@ -0,0 +1,131 @@
+use crate::storage;
+use bridge::{MatchId, MatchRecord, UserId};
+use log::{error, warn};
+use serde::Deserialize;
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, ErrorKind, Write};
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+
+/// One record per line, appended as rounds finish.
+const FILE: &str = "matches.log";
+/// The store before the log, carried over into it once.
+const LEGACY_FILE: &str = "matches.json";
+/// Rounds kept in memory for lookups. Older ones stay in the log only.
+const MAX_RECORDS: usize = 10_000;
+
+#[derive(Deserialize, Default)]
+struct Legacy {
+    next_id: MatchId,
+    records: Vec<MatchRecord>,
+}
+
+/// Finished rounds, oldest first. New ones are written out on a thread of
+/// their own, so the host never waits on the disk.
+pub struct Matches {
+    next_id: MatchId,
+    records: VecDeque<MatchRecord>,
+    log: Sender<MatchRecord>,
+}
+
+/// Keeps nothing on disk.
+impl Default for Matches {
+    fn default() -> Self {
+        Self {
+            next_id: 0,
+            records: VecDeque::new(),
+            log: mpsc::channel().0,
+        }
+    }
+}
+
+fn append(record: &MatchRecord) -> io::Result<()> {
+    let path = storage::path(FILE);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut line = serde_json::to_vec(record)?;
+    line.push(b'\n');
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?
+        .write_all(&line)
+}
+
+fn start_log() -> Sender<MatchRecord> {
+    let (sender, receiver) = mpsc::channel::<MatchRecord>();
+    thread::spawn(move || {
+        for record in receiver {
+            if let Err(e) = append(&record) {
+                error!("Failed to save match #{}: {}", record.id, e);
+            }
+        }
+    });
+    sender
+}
+
+impl Matches {
+    pub fn load() -> io::Result<Self> {
+        let mut matches = Self {
+            log: start_log(),
+            ..Self::default()
+        };
+        match File::open(storage::path(FILE)) {
+            Ok(file) => {
+                for line in BufReader::new(file).lines() {
+                    let line = line?;
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    // A line cut short by a crash costs only that round.
+                    match serde_json::from_str::<MatchRecord>(&line) {
+                        Ok(record) => {
+                            matches.next_id = matches.next_id.max(record.id);
+                            matches.keep(record);
+                        }
+                        Err(e) => warn!("Skipped a broken match record: {}", e),
+                    }
+                }
+            }
+            Err(ref e) if e.kind() == ErrorKind::NotFound => {
+                let legacy: Legacy = storage::load(LEGACY_FILE)?;
+                matches.next_id = legacy.next_id;
+                for record in legacy.records {
+                    let _ = matches.log.send(record.clone());
+                    matches.keep(record);
+                }
+            }
+            Err(e) => return Err(e),
+        }
+        Ok(matches)
+    }
+
+    fn keep(&mut self, record: MatchRecord) {
+        if self.records.len() >= MAX_RECORDS {
+            self.records.pop_front();
+        }
+        self.records.push_back(record);
+    }
+
+    /// Stores a finished round, filling in its id.
+    pub fn record(&mut self, mut record: MatchRecord) -> MatchRecord {
+        self.next_id += 1;
+        record.id = self.next_id;
+        let _ = self.log.send(record.clone());
+        self.keep(record.clone());
+        record
+    }
+
+    /// The latest rounds `user` played in, newest first.
+    pub fn recent(&self, user: UserId, limit: usize) -> Vec<MatchRecord> {
+        self.records
+            .iter()
+            .rev()
+            .filter(|record| record.players.iter().any(|player| player.id == user))
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+}
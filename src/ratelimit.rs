@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+use std::time::Instant;
+
+/// How many messages a connection may send at once, and how fast that
+/// allowance comes back.
+pub struct Limit {
+    pub burst: f64,
+    pub per_second: f64,
+}
+
+/// People type in bursts, so they get room for one.
+pub const PERSON: Limit = Limit {
+    burst: 20.0,
+    per_second: 5.0,
+};
+
+/// Bot accounts send at a steady pace and get less slack.
+pub const BOT: Limit = Limit {
+    burst: 10.0,
+    per_second: 2.0,
+};
+
+/// Logins and sign-ups, each of which hashes a password, per address and
+/// per name.
+pub const LOGIN: Limit = Limit {
+    burst: 10.0,
+    per_second: 0.2,
+};
+
+/// Keys past this many make the throttle forget the quiet ones.
+const MAX_KEYS: usize = 10_000;
+
+/// A token bucket: each message takes a token, and tokens refill over time
+/// up to the burst size.
+pub struct Bucket {
+    limit: &'static Limit,
+    tokens: f64,
+    last: Instant,
+}
+
+impl Bucket {
+    pub fn new(limit: &'static Limit) -> Self {
+        Self {
+            limit,
+            tokens: limit.burst,
+            last: Instant::now(),
+        }
+    }
+
+    fn refilled(&self, now: Instant) -> f64 {
+        let elapsed = now.duration_since(self.last);
+        let elapsed = elapsed.as_secs() as f64 + f64::from(elapsed.subsec_millis()) / 1000.0;
+        (self.tokens + elapsed * self.limit.per_second).min(self.limit.burst)
+    }
+
+    /// Whether a message may go through now.
+    pub fn take(&mut self, now: Instant) -> bool {
+        self.tokens = self.refilled(now);
+        self.last = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// A bucket for each key, such as an address or a name.
+pub struct Throttle {
+    limit: &'static Limit,
+    buckets: HashMap<String, Bucket>,
+}
+
+impl Throttle {
+    pub fn new(limit: &'static Limit) -> Self {
+        Self {
+            limit,
+            buckets: HashMap::new(),
+        }
+    }
+
+    /// Whether `key` may go ahead now.
+    pub fn take(&mut self, key: String, now: Instant) -> bool {
+        let limit = self.limit;
+        if self.buckets.len() >= MAX_KEYS {
+            // A full bucket is no different from a new one.
+            self.buckets
+                .retain(|_, bucket| bucket.refilled(now) < limit.burst);
+        }
+        self.buckets
+            .entry(key)
+            .or_insert_with(|| Bucket::new(limit))
+            .take(now)
+    }
+}
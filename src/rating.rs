@@ -0,0 +1,119 @@
+use crate::storage::{self, Writer};
+use bridge::{GameType, UserId};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io;
+
+const FILE: &str = "ratings.json";
+pub const INITIAL_RATING: f64 = 1500.0;
+/// The most a rating moves in one round.
+const K_FACTOR: f64 = 32.0;
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct Rating {
+    pub rating: f64,
+    pub games: u32,
+}
+
+impl Default for Rating {
+    fn default() -> Self {
+        Self {
+            rating: INITIAL_RATING,
+            games: 0,
+        }
+    }
+}
+
+/// Where a player finished in a round. Higher is better.
+pub type Placing = (bool, u32);
+
+/// Elo ratings of accounts, kept separately for each game.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct Ratings {
+    games: BTreeMap<GameType, BTreeMap<UserId, Rating>>,
+    #[serde(skip)]
+    writer: Writer,
+}
+
+fn expected(rating: f64, other: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf((other - rating) / 400.0))
+}
+
+impl Ratings {
+    pub fn load() -> io::Result<Self> {
+        let mut ratings: Self = storage::load(FILE)?;
+        ratings.writer = Writer::start();
+        Ok(ratings)
+    }
+
+    /// Saves in the background, as every round changes some ratings.
+    pub fn save(&self) {
+        self.writer.save(FILE, self.clone());
+    }
+
+    pub fn flush(&self) {
+        self.writer.flush();
+    }
+
+    pub fn get(&self, game: GameType, user: UserId) -> Rating {
+        self.games
+            .get(&game)
+            .and_then(|ratings| ratings.get(&user))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Scores a round as a match between every pair of players in it.
+    pub fn update(&mut self, game: GameType, players: &[(UserId, Placing)]) {
+        if players.len() < 2 {
+            return;
+        }
+        let before = players
+            .iter()
+            .map(|&(id, _)| self.get(game, id).rating)
+            .collect::<Vec<_>>();
+        let ratings = self.games.entry(game).or_insert_with(BTreeMap::new);
+        let opponents = (players.len() - 1) as f64;
+        for (i, &(id, placing)) in players.iter().enumerate() {
+            let mut delta = 0.0;
+            for (j, &(_, other)) in players.iter().enumerate() {
+                if i == j {
+                    continue;
+                }
+                let actual = if placing > other {
+                    1.0
+                } else if placing == other {
+                    0.5
+                } else {
+                    0.0
+                };
+                delta += actual - expected(before[i], before[j]);
+            }
+            let rating = ratings.entry(id).or_insert_with(Rating::default);
+            rating.rating += K_FACTOR * delta / opponents;
+            rating.games += 1;
+        }
+        self.save();
+    }
+
+    /// The best rated players of `game` that `listed` lets in, best first.
+    pub fn top<F>(&self, game: GameType, limit: usize, listed: F) -> Vec<(UserId, Rating)>
+    where
+        F: Fn(UserId) -> bool,
+    {
+        let mut top: Vec<(UserId, Rating)> = self
+            .games
+            .get(&game)
+            .map(|ratings| {
+                ratings
+                    .iter()
+                    .filter(|(&id, _)| listed(id))
+                    .map(|(&id, &rating)| (id, rating))
+                    .collect()
+            })
+            .unwrap_or_else(Vec::new);
+        top.sort_by(|(_, a), (_, b)| b.rating.partial_cmp(&a.rating).unwrap());
+        top.truncate(limit);
+        top
+    }
+}
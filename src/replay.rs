@@ -0,0 +1,30 @@
+use crate::storage::{self, Writer};
+use bridge::{MatchId, Replay};
+use log::error;
+use std::fs;
+use std::io::{self, ErrorKind};
+
+const DIR: &str = "replays";
+
+fn name(id: MatchId) -> String {
+    format!("{}/{}.json", DIR, id)
+}
+
+/// Replays get a file each, named after their match.
+pub fn save(writer: &Writer, replay: Replay) {
+    writer.run(move || {
+        let saved = fs::create_dir_all(storage::path(DIR))
+            .and_then(|_| storage::save(&name(replay.record.id), &replay));
+        if let Err(e) = saved {
+            error!("Failed to save replay #{}: {}", replay.record.id, e);
+        }
+    });
+}
+
+pub fn load(id: MatchId) -> io::Result<Option<Replay>> {
+    match fs::read(storage::path(&name(id))) {
+        Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+        Err(ref e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
@@ -0,0 +1,62 @@
+use crate::storage;
+use bridge::api::{Report, Resolution};
+use bridge::{ChatId, ReportId, UserId};
+use log::error;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io;
+
+const FILE: &str = "reports.json";
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct Reports {
+    next_id: ReportId,
+    reports: BTreeMap<ReportId, Report>,
+}
+
+impl Reports {
+    pub fn load() -> io::Result<Self> {
+        storage::load(FILE)
+    }
+
+    pub fn save(&self) {
+        if let Err(e) = storage::save(FILE, self) {
+            error!("Failed to save reports: {}", e);
+        }
+    }
+
+    /// Stores a new report, filling in its id.
+    pub fn file(&mut self, mut report: Report) -> ReportId {
+        self.next_id += 1;
+        report.id = self.next_id;
+        self.reports.insert(report.id, report);
+        self.save();
+        self.next_id
+    }
+
+    /// Whether `reporter` already reported the line `message_id`.
+    pub fn filed(&self, reporter: UserId, message_id: ChatId) -> bool {
+        self.reports
+            .values()
+            .any(|report| report.reporter == reporter && report.message_id == message_id)
+    }
+
+    pub fn list(&self, all: bool) -> Vec<Report> {
+        self.reports
+            .values()
+            .filter(|report| all || report.resolution.is_none())
+            .cloned()
+            .collect()
+    }
+
+    pub fn resolve(&mut self, id: ReportId, resolution: Resolution) -> bool {
+        match self.reports.get_mut(&id) {
+            Some(report) => {
+                report.resolution = Some(resolution);
+                self.save();
+                true
+            }
+            None => false,
+        }
+    }
+}
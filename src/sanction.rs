@@ -0,0 +1,73 @@
+use crate::storage;
+use bridge::api::{Sanction, SanctionTarget};
+use bridge::{SanctionId, SanctionKind, UserId};
+use log::error;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io;
+
+const FILE: &str = "sanctions.json";
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct Sanctions {
+    next_id: SanctionId,
+    sanctions: BTreeMap<SanctionId, Sanction>,
+}
+
+impl Sanctions {
+    pub fn load() -> io::Result<Self> {
+        storage::load(FILE)
+    }
+
+    pub fn save(&self) {
+        if let Err(e) = storage::save(FILE, self) {
+            error!("Failed to save sanctions: {}", e);
+        }
+    }
+
+    /// Stores a new sanction, filling in its id.
+    pub fn add(&mut self, mut sanction: Sanction) -> Sanction {
+        self.next_id += 1;
+        sanction.id = self.next_id;
+        self.sanctions.insert(sanction.id, sanction.clone());
+        self.save();
+        sanction
+    }
+
+    pub fn lift(&mut self, id: SanctionId) -> Option<Sanction> {
+        let sanction = self.sanctions.get_mut(&id)?;
+        sanction.lifted = true;
+        let sanction = sanction.clone();
+        self.save();
+        Some(sanction)
+    }
+
+    /// Sanctions in force at `now`, or every one ever given with `all`.
+    pub fn list(&self, all: bool, now: u64) -> Vec<Sanction> {
+        self.sanctions
+            .values()
+            .filter(|sanction| all || (!sanction.lifted && sanction.until > now))
+            .cloned()
+            .collect()
+    }
+
+    /// The longest-running sanction that forbids `kind` for an account or
+    /// an address.
+    pub fn find(
+        &self,
+        kind: SanctionKind,
+        account: Option<UserId>,
+        ip: Option<&str>,
+        now: u64,
+    ) -> Option<&Sanction> {
+        self.sanctions
+            .values()
+            .filter(|sanction| !sanction.lifted && sanction.until > now)
+            .filter(|sanction| sanction.kind.covers(kind))
+            .filter(|sanction| match &sanction.target {
+                SanctionTarget::Account(id) => account == Some(*id),
+                SanctionTarget::Ip(address) => ip == Some(address.as_str()),
+            })
+            .max_by_key(|sanction| sanction.until)
+    }
+}
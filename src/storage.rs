@@ -0,0 +1,84 @@
+use log::error;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+
+const DATA_DIR: &str = "data";
+
+pub fn path(name: &str) -> PathBuf {
+    Path::new(DATA_DIR).join(name)
+}
+
+/// Reads a JSON file from the data directory. A missing file is an empty
+/// store, but a broken one is an error so that it never gets overwritten.
+pub fn load<T: DeserializeOwned + Default>(name: &str) -> io::Result<T> {
+    match fs::read(path(name)) {
+        Ok(bytes) => Ok(serde_json::from_slice(&bytes)?),
+        Err(ref e) if e.kind() == ErrorKind::NotFound => Ok(T::default()),
+        Err(e) => Err(e),
+    }
+}
+
+pub fn save<T: Serialize>(name: &str, value: &T) -> io::Result<()> {
+    fs::create_dir_all(DATA_DIR)?;
+    let temp = path(&format!("{}.tmp", name));
+    fs::write(&temp, serde_json::to_vec(value)?)?;
+    fs::rename(temp, path(name))
+}
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// Writes files on a thread of its own, in the order they were handed
+/// over, so the host never waits on the disk.
+#[derive(Clone)]
+pub struct Writer {
+    jobs: Sender<Job>,
+}
+
+/// Writes nothing, for stores that were never loaded from disk.
+impl Default for Writer {
+    fn default() -> Self {
+        Self {
+            jobs: mpsc::channel().0,
+        }
+    }
+}
+
+impl Writer {
+    pub fn start() -> Self {
+        let (jobs, receiver) = mpsc::channel::<Job>();
+        thread::spawn(move || {
+            for job in receiver {
+                job();
+            }
+        });
+        Self { jobs }
+    }
+
+    pub fn run<F: FnOnce() + Send + 'static>(&self, job: F) {
+        let _ = self.jobs.send(Box::new(job));
+    }
+
+    /// Saves `value` as `name` after everything handed over before it.
+    pub fn save<T: Serialize + Send + 'static>(&self, name: &str, value: T) {
+        let name = name.to_string();
+        self.run(move || {
+            if let Err(e) = save(&name, &value) {
+                error!("Failed to save {}: {}", name, e);
+            }
+        });
+    }
+
+    /// Waits until everything handed over so far is written.
+    pub fn flush(&self) {
+        let (done, wait) = mpsc::channel();
+        self.run(move || {
+            let _ = done.send(());
+        });
+        let _ = wait.recv();
+    }
+}